@@ -0,0 +1,83 @@
+use std::{fs, io, net::SocketAddr, path::{Path, PathBuf}};
+
+use serde::Deserialize;
+
+use crate::{library_store::is_valid_profile_id, mock::Profile};
+
+/// Settings read once at startup, everything optional so an empty or missing file is fine
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// The remote control server only runs when this section is present
+    pub remote: Option<RemoteConfig>,
+    /// Unlocks the parents' features on the device, like editing the library. They stay locked
+    /// without one.
+    pub pin: Option<String>,
+    /// Kids sharing the device, each picks their own profile on startup. Without any, everyone
+    /// shares the same favourites, resume points and limits.
+    pub profiles: Vec<Profile>,
+    pub podcasts: PodcastConfig,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct RemoteConfig {
+    /// Defaults to every interface, phones reach the player over the home network
+    #[serde(default = "default_listen")]
+    pub listen: SocketAddr,
+    /// Sent as `Authorization: Bearer <token>` or `?token=<token>`, browsers cannot set headers
+    /// on WebSockets
+    pub token: String,
+}
+
+fn default_listen() -> SocketAddr {
+    SocketAddr::from(([0, 0, 0, 0], 8080))
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct PodcastConfig {
+    /// Downloaded episodes never take more than this, the rest streams
+    pub quota_mb: u64,
+    /// How often feeds are checked for new episodes
+    pub refresh_minutes: u64,
+}
+
+impl Default for PodcastConfig {
+    fn default() -> Self {
+        PodcastConfig { quota_mb: 2048, refresh_minutes: 60 }
+    }
+}
+
+impl Config {
+    /// `path` or `$XDG_CONFIG_HOME/rust-slint-music-player/config.json`
+    pub fn load(path: Option<&Path>) -> io::Result<Self> {
+        let Some(path) = path.map(Path::to_path_buf).or_else(config_path) else {
+            return Ok(Config::default());
+        };
+        let config: Config = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), error)))?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(error) => return Err(error),
+        };
+        config.check_profiles().map_err(|error| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), error)))?;
+        Ok(config)
+    }
+
+    /// Profile ids name files, they have to be usable as such and must not clash
+    fn check_profiles(&self) -> Result<(), String> {
+        for (index, profile) in self.profiles.iter().enumerate() {
+            if !is_valid_profile_id(&profile.id) {
+                return Err(format!("profile id {:?} cannot be used as a file name", profile.id));
+            }
+            if self.profiles[..index].iter().any(|other| other.id == profile.id) {
+                return Err(format!("profile id {:?} is used twice", profile.id));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn config_path() -> Option<PathBuf> {
+    Some(crate::xdg::config_directory()?.join("config.json"))
+}
@@ -0,0 +1,276 @@
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+    sync::mpsc::channel,
+    time::Duration,
+};
+
+use crate::{
+    mock::{self, get_library_entry, LibraryEntry, Variant, ROOT_LIBRARY_ENTRY_ID},
+    model::{actions::Unlock, Action, Field, State},
+    podcasts::Podcasts,
+};
+
+const HELP: &str = "\
+ls [id]        Kinder des aktuellen oder angegebenen Eintrags
+cd <id|..|/>   Eintrag öffnen, zurück oder nach Hause
+play <id>      Eintrag abspielen
+pause          Wiedergabe anhalten
+resume         Wiedergabe fortsetzen
+seek <s>       Zu Sekunde s springen
+queue [id]     Warteschlange zeigen oder Eintrag anhängen
+next <id>      Eintrag als Nächstes abspielen
+search <text>  Bibliothek durchsuchen
+volume <0-100> Lautstärke setzen
+lock           Kindersperre einschalten
+unlock <pin>   Kindersperre mit der PIN aufheben
+extra <pin> <min>  Heute mehr Zeit zum Hören geben
+profile [id]   Profile zeigen oder zu einem wechseln
+podcast add <url>  Podcast im aktuellen Ordner abonnieren
+podcast refresh    Alle Podcasts aktualisieren und Folgen laden
+state          Zustand ausgeben
+sleep <s>      s Sekunden warten, etwa damit die Wiedergabe weiterläuft
+quit           Beenden";
+
+/// Runs the model and the player without a window. Commands come from `script` when given,
+/// one per line, otherwise from stdin. Every state change is printed as it happens.
+pub fn run(state: State, podcasts: Podcasts, script: Option<PathBuf>) -> io::Result<()> {
+    state.subscribe(|changes| {
+        for field in changes.iter() {
+            println!("  ~ {}", describe(field));
+        }
+    });
+
+    match script {
+        Some(path) => {
+            let script = fs::read_to_string(&path)?;
+            for (number, line) in script.lines().enumerate() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                println!("> {}", line);
+                match run_command(&state, &podcasts, line) {
+                    Ok(true) => {}
+                    Ok(false) => break,
+                    // A broken script should fail the check that runs it
+                    Err(error) => {
+                        return Err(io::Error::other(format!("{}:{}: {}", path.display(), number + 1, error)));
+                    }
+                }
+            }
+        }
+        None => {
+            println!("Tippe \"help\" für eine Liste der Befehle");
+            let stdin = io::stdin();
+            loop {
+                print!("> ");
+                io::stdout().flush()?;
+                let mut line = String::new();
+                if stdin.lock().read_line(&mut line)? == 0 {
+                    break;
+                }
+                match run_command(&state, &podcasts, line.trim()) {
+                    Ok(true) => {}
+                    Ok(false) => break,
+                    Err(error) => println!("Fehler: {}", error),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether to keep going
+fn run_command(state: &State, podcasts: &Podcasts, line: &str) -> Result<bool, String> {
+    let (command, argument) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let argument = argument.trim();
+
+    match command {
+        "" => {}
+        "help" => println!("{}", HELP),
+        "quit" | "exit" => return Ok(false),
+        "ls" => {
+            let id = match argument {
+                "" => state.active_library_entry().map(|entry| entry.id).unwrap_or(ROOT_LIBRARY_ENTRY_ID),
+                argument => parse_id(argument)?,
+            };
+            let entry = get_library_entry(id).map_err(|error| error.to_string())?;
+            let loaded_id = state.playing_library_entry().map(|entry| entry.id);
+            for child in entry.children.unwrap_or_default() {
+                let marker = if Some(child.id) == loaded_id { "▶" } else { " " };
+                println!("{} {:>4}  {:<8} {}{}", marker, child.id, format!("{:?}", child.variant), child.name, episode_state(&child));
+            }
+        }
+        "cd" => {
+            let action = match argument {
+                "" | "/" => Action::GoHome,
+                ".." => Action::GoBack,
+                argument => Action::LoadLibraryEntry(parse_id(argument)?),
+            };
+            dispatch(state, action);
+        }
+        "play" => {
+            let entry = get_library_entry(parse_id(argument)?).map_err(|error| error.to_string())?;
+            dispatch(state, Action::PlayLibraryEntry(entry));
+        }
+        "pause" => {
+            if state.is_playing() {
+                dispatch(state, Action::TogglePlay(true));
+            }
+        }
+        "resume" => {
+            if !state.is_playing() && state.playing_library_entry().is_some() {
+                dispatch(state, Action::TogglePlay(false));
+            }
+        }
+        "seek" => {
+            let seconds: u64 = argument.parse().map_err(|_| format!("\"{}\" ist keine Sekundenzahl", argument))?;
+            dispatch(state, Action::Seek(Duration::from_secs(seconds)));
+        }
+        "queue" => match argument {
+            "" => {
+                for (index, entry) in state.queue().iter().enumerate() {
+                    println!("{:>3}. {:>4}  {}", index + 1, entry.id, entry.name);
+                }
+            }
+            argument => dispatch(state, Action::AddToQueue(parse_id(argument)?)),
+        },
+        "next" => dispatch(state, Action::PlayNext(parse_id(argument)?)),
+        "search" => {
+            dispatch(state, Action::Search(argument.to_string()));
+            for entry in state.search_results() {
+                println!("  {:>4}  {:<8} {} ({})", entry.id, format!("{:?}", entry.variant), entry.name, entry.parent_name.unwrap_or_default());
+            }
+        }
+        "volume" => {
+            let percent: f32 = argument.parse().map_err(|_| format!("\"{}\" ist keine Prozentzahl", argument))?;
+            dispatch(state, Action::SetVolume(percent / 100.0));
+        }
+        "lock" => dispatch(state, Action::Lock),
+        "unlock" => dispatch(state, Action::Unlock(Unlock::Pin(argument.to_string()))),
+        "extra" => {
+            let (pin, minutes) = argument.split_once(char::is_whitespace).ok_or("Erst die PIN, dann die Minuten")?;
+            let minutes: u32 = minutes.trim().parse().map_err(|_| format!("\"{}\" ist keine Minutenzahl", minutes.trim()))?;
+            dispatch(state, Action::GrantExtraMinutes(pin.to_string(), minutes));
+        }
+        "profile" => match argument {
+            "" => {
+                let active = state.profile().map(|profile| profile.id);
+                for profile in state.profiles() {
+                    let marker = if Some(&profile.id) == active.as_ref() { "*" } else { " " };
+                    println!("{} {:<12} {}", marker, profile.id, profile.name);
+                }
+            }
+            id => dispatch(state, Action::SwitchProfile(id.to_string())),
+        },
+        "podcast" => {
+            let (subcommand, url) = argument.split_once(char::is_whitespace).unwrap_or((argument, ""));
+            // The shell runs on a blocking thread, the runtime is free to drive the request
+            let runtime = tokio::runtime::Handle::current();
+            match subcommand {
+                "add" => {
+                    let parent_id = mock::real_id(state.active_library_entry().map(|entry| entry.id).unwrap_or(ROOT_LIBRARY_ENTRY_ID));
+                    let id = runtime.block_on(podcasts.subscribe(parent_id, url)).map_err(|error| error.to_string())?;
+                    dispatch(state, Action::LoadLibraryEntry(id));
+                }
+                "refresh" => {
+                    if runtime.block_on(podcasts.refresh()) {
+                        dispatch(state, Action::LibraryChanged);
+                    }
+                }
+                subcommand => return Err(format!("\"podcast {}\" gibt es nicht, nur add und refresh", subcommand)),
+            }
+        }
+        "state" => print_state(state),
+        "sleep" => {
+            let seconds: f64 = argument.parse().map_err(|_| format!("\"{}\" ist keine Sekundenzahl", argument))?;
+            std::thread::sleep(Duration::from_secs_f64(seconds.max(0.0)));
+        }
+        command => return Err(format!("Unbekannter Befehl \"{}\", \"help\" zeigt alle", command)),
+    }
+    Ok(true)
+}
+
+fn parse_id(argument: &str) -> Result<i32, String> {
+    argument.parse().map_err(|_| format!("\"{}\" ist keine Id", argument))
+}
+
+/// Dispatches and waits until the action has been handled, so the next command sees its outcome
+fn dispatch(state: &State, action: Action) {
+    let (done_tx, done_rx) = channel();
+    state.dispatch(action);
+    state.dispatch(Action::Sync(done_tx));
+    done_rx.recv().ok();
+}
+
+fn print_state(state: &State) {
+    println!("profile:   {}", state.profile().map_or("-".to_string(), |profile| profile.name));
+    println!("active:    {}", describe_entry(state.active_library_entry().as_ref()));
+    let playing = state.playing_library_entry();
+    println!("playing:   {}", describe_entry(playing.as_ref()));
+    println!("source:    {}", playing.and_then(|entry| entry.track_source).and_then(|source| source.location()).unwrap_or_else(|| "-".to_string()));
+    println!("is_playing: {}", state.is_playing());
+    let progress = state.progress();
+    println!("progress:  {}s / {}s", progress.position().as_secs(), progress.duration().as_secs());
+    println!("queue:     {} entries", state.queue().len());
+    println!("volume:    {}%", (state.volume() * 100.0).round());
+    println!("locked:    {}", state.locked());
+    println!("time up:   {}", state.time_is_up());
+    let navigation = state.navigation();
+    println!("history:   {} back, {} forward", navigation.back.len(), navigation.forward.len());
+    for message in state.messages() {
+        println!("message:   [{:?}] {}", message.severity, message.text);
+    }
+}
+
+/// Whether an episode is new and already downloaded, nothing for anything else
+fn episode_state(entry: &LibraryEntry) -> String {
+    if entry.variant != Variant::Episode {
+        return String::new();
+    }
+    let downloaded = entry.track_source.as_ref().is_some_and(|source| source.local_path.is_some());
+    format!("{}{}", if entry.unplayed { "  (neu)" } else { "" }, if downloaded { "  [geladen]" } else { "" })
+}
+
+fn describe_entry(entry: Option<&LibraryEntry>) -> String {
+    match entry {
+        Some(entry) => format!("{} \"{}\"", entry.id, entry.name),
+        None => "-".to_string(),
+    }
+}
+
+/// One line per change, `Debug` would dump whole covers
+fn describe(field: &Field) -> String {
+    match field {
+        Field::messages(messages) => format!(
+            "messages: [{}]",
+            messages.iter().map(|message| format!("{:?} \"{}\"", message.severity, message.text)).collect::<Vec<_>>().join(", ")
+        ),
+        Field::active_library_entry(entry) => format!("active_library_entry: {}", describe_entry(entry.as_ref())),
+        Field::navigation(navigation) => format!(
+            "navigation: {:?} ({} back, {} forward)",
+            navigation.current.map(|location| location.id), navigation.back.len(), navigation.forward.len()
+        ),
+        Field::playing_library_entry(entry) => format!("playing_library_entry: {}", describe_entry(entry.as_ref())),
+        Field::is_playing(is_playing) => format!("is_playing: {}", is_playing),
+        Field::progress(progress) => format!("progress: {}s / {}s", progress.position().as_secs(), progress.duration().as_secs()),
+        Field::queue(queue) => format!(
+            "queue: [{}]",
+            queue.iter().map(|entry| entry.id.to_string()).collect::<Vec<_>>().join(", ")
+        ),
+        Field::resume_positions(positions) => format!("resume_positions: {} entries", positions.len()),
+        Field::search_query(query) => format!("search_query: \"{}\"", query),
+        Field::search_results(results) => format!("search_results: {} entries", results.len()),
+        Field::edit_mode(edit_mode) => format!("edit_mode: {}", edit_mode),
+        Field::undo_stack(steps) => format!(
+            "undo_stack: [{}]",
+            steps.iter().map(|step| step.label.as_str()).collect::<Vec<_>>().join(", ")
+        ),
+        Field::volume(volume) => format!("volume: {}%", (volume * 100.0).round()),
+        Field::locked(locked) => format!("locked: {}", locked),
+        Field::time_is_up(time_is_up) => format!("time_is_up: {}", time_is_up),
+        Field::profile(profile) => format!("profile: {}", profile.as_ref().map_or("-", |profile| profile.name.as_str())),
+    }
+}
@@ -0,0 +1,160 @@
+use std::sync::{Arc, OnceLock};
+
+use image::{imageops, RgbaImage};
+use resvg::{tiny_skia, usvg};
+use slint::SharedPixelBuffer;
+
+use crate::mock::{get_library_entry, LibraryEntry, Variant};
+
+use super::{thumbnail, CacheKey, Thumbnail, ThumbnailCache};
+
+/// Background colours for generated artwork, dark enough for white text on top
+const PALETTE: [&str; 10] = [
+    "#c62828", "#ad1457", "#6a1b9a", "#4527a0", "#283593",
+    "#1565c0", "#00695c", "#2e7d32", "#d84315", "#4e342e",
+];
+
+// Material Design icons, drawn on a 24×24 grid
+const NOTE_ICON: &str = "M12 3v10.55c-.59-.34-1.27-.55-2-.55-2.21 0-4 1.79-4 4s1.79 4 4 4 4-1.79 4-4V7h4V3h-6z";
+const RADIO_ICON: &str = "M3.24 6.15C2.51 6.43 2 7.17 2 8v12c0 1.1.89 2 2 2h16c1.11 0 2-.9 2-2V8c0-1.11-.89-2-2-2H8.3l8.26-3.34L15.88 1 3.24 6.15zM7 20c-1.66 0-3-1.34-3-3s1.34-3 3-3 3 1.34 3 3-1.34 3-3 3zm13-8h-2v-2h-2v2H4V8h16v4z";
+const MICROPHONE_ICON: &str = "M12 14c1.66 0 2.99-1.34 2.99-3L15 5c0-1.66-1.34-3-3-3S9 3.34 9 5v6c0 1.66 1.34 3 3 3zm5.3-3c0 3-2.54 5.1-5.3 5.1S6.7 14 6.7 11H5c0 3.41 2.72 6.23 6 6.72V21h2v-3.28c3.28-.48 6-3.3 6-6.72h-1.7z";
+const FOLDER_ICON: &str = "M10 4H4c-1.1 0-1.99.9-1.99 2L2 18c0 1.1.9 2 2 2h16c1.1 0 2-.9 2-2V8c0-1.1-.9-2-2-2h-8l-2-2z";
+
+/// The same entry always gets the same colour
+fn background_color(id: i32) -> &'static str {
+    // Knuth's multiplicative hash spreads neighbouring ids over the palette
+    let index = (id as u32).wrapping_mul(2654435761) as usize % PALETTE.len();
+    PALETTE[index]
+}
+
+/// First letter of the first two words, "Die drei ???" becomes "DD"
+fn initials(name: &str) -> String {
+    name.split_whitespace()
+        .filter_map(|word| word.chars().find(|c| c.is_alphanumeric()))
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+fn artwork_svg(entry: &LibraryEntry) -> String {
+    let background = background_color(entry.id);
+    let initials = initials(&entry.name);
+    let icon = match entry.variant {
+        Variant::Folder if !initials.is_empty() => None,
+        Variant::Folder => Some(FOLDER_ICON),
+        Variant::Stream => Some(RADIO_ICON),
+        Variant::Podcast => Some(MICROPHONE_ICON),
+        Variant::File | Variant::Spotify | Variant::Episode => Some(NOTE_ICON),
+    };
+
+    let foreground = match icon {
+        Some(icon) => format!(r##"<path transform="translate(26 26) scale(2)" fill="#ffffff" d="{}"/>"##, icon),
+        // Initials are alphanumeric only, nothing to escape
+        None => format!(
+            r##"<text x="50" y="50" font-family="sans-serif" font-weight="bold" font-size="40" fill="#ffffff" text-anchor="middle" dominant-baseline="central">{}</text>"##,
+            initials
+        ),
+    };
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100" viewBox="0 0 100 100"><rect width="100" height="100" fill="{}"/>{}</svg>"##,
+        background, foreground
+    )
+}
+
+/// System fonts take a while to load, so this happens once
+fn font_database() -> Arc<usvg::fontdb::Database> {
+    static FONTS: OnceLock<Arc<usvg::fontdb::Database>> = OnceLock::new();
+    FONTS.get_or_init(|| {
+        let mut fonts = usvg::fontdb::Database::new();
+        fonts.load_system_fonts();
+
+        // fontdb maps sans-serif to Arial, which a Raspberry Pi usually does not have
+        let query = usvg::fontdb::Query { families: &[usvg::fontdb::Family::SansSerif], ..Default::default() };
+        if fonts.query(&query).is_none() {
+            let fallback = fonts.faces()
+                .find(|face| face.families.iter().any(|(family, _)| family.contains("Sans")))
+                .or_else(|| fonts.faces().next())
+                .and_then(|face| face.families.first())
+                .map(|(family, _)| family.clone());
+            if let Some(family) = fallback {
+                fonts.set_sans_serif_family(family);
+            }
+        }
+        Arc::new(fonts)
+    }).clone()
+}
+
+fn render_svg(svg: &str, size: u32) -> Result<Thumbnail, usvg::Error> {
+    let options = usvg::Options { fontdb: font_database(), ..Default::default() };
+    let tree = usvg::Tree::from_str(svg, &options)?;
+
+    let mut pixmap = tiny_skia::Pixmap::new(size, size).ok_or(usvg::Error::InvalidSize)?;
+    let scale = size as f32 / tree.size().width();
+    resvg::render(&tree, tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+
+    // tiny-skia works premultiplied, the rest of the pipeline expects straight alpha
+    let pixels: Vec<u8> = pixmap.pixels().iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+    Ok(SharedPixelBuffer::clone_from_slice(&pixels, size, size))
+}
+
+/// Deterministic artwork for entries without a cover: initials for folders, an icon otherwise,
+/// on a colour derived from the entry id
+pub fn generated_artwork(entry: &LibraryEntry, size: u32) -> Thumbnail {
+    let svg = artwork_svg(entry);
+    let key = CacheKey::new(svg.as_bytes(), size);
+    ThumbnailCache::global()
+        .get_or_create(key, || {
+            render_svg(&svg, size).map_err(|error| {
+                image::ImageError::Decoding(image::error::DecodingError::new(
+                    image::error::ImageFormatHint::Name("svg".to_string()),
+                    error,
+                ))
+            })
+        })
+        .unwrap_or_else(|error| {
+            eprintln!("Could not render artwork of library entry {}: {}", entry.id, error);
+            super::placeholder_thumbnail(size)
+        })
+}
+
+/// 2×2 grid of the first four child covers, for folders that have no cover themselves
+pub fn folder_mosaic(entry: &LibraryEntry, size: u32) -> Option<Thumbnail> {
+    if !matches!(entry.variant, Variant::Folder) {
+        return None;
+    }
+
+    let children = get_library_entry(entry.id).ok()?.children?;
+    let covers: Vec<&[u8]> = children.iter().filter_map(|child| child.image.as_deref()).take(4).collect();
+    if covers.len() < 4 {
+        return None;
+    }
+
+    // Key the mosaic by the covers it is made of, so it updates when a child cover changes
+    let mut key_source = b"mosaic".to_vec();
+    for cover in covers.iter() {
+        key_source.extend_from_slice(&CacheKey::new(cover, size).content_hash.to_le_bytes());
+    }
+    let key = CacheKey::new(&key_source, size);
+
+    ThumbnailCache::global()
+        .get_or_create(key, || {
+            let half = size / 2;
+            let mut mosaic = RgbaImage::new(size, size);
+            for (index, cover) in covers.iter().enumerate() {
+                let tile = thumbnail(cover, half)?;
+                let tile = RgbaImage::from_raw(tile.width(), tile.height(), tile.as_bytes().to_vec())
+                    .expect("thumbnail buffers are always RGBA8");
+                let (x, y) = ((index as u32 % 2) * half, (index as u32 / 2) * half);
+                imageops::replace(&mut mosaic, &tile, x as i64, y as i64);
+            }
+            Ok(SharedPixelBuffer::clone_from_slice(mosaic.as_raw(), size, size))
+        })
+        .ok()
+}
+
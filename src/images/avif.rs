@@ -0,0 +1,167 @@
+//! AVIF covers, decoded by the system's libheif. The `image` crate only decodes AVIF against a
+//! libdav1d it links at build time, libheif is loaded instead once the first AVIF cover shows up.
+
+use std::{
+    ffi::{c_char, c_int, c_void, CStr},
+    ptr,
+    sync::OnceLock,
+};
+
+use image::{
+    error::{DecodingError, ImageFormatHint, UnsupportedError, UnsupportedErrorKind},
+    ImageError, ImageFormat, RgbaImage,
+};
+use libloading::Library;
+
+const LIBRARY_NAMES: [&str; 2] = ["libheif.so.1", "libheif.so"];
+
+// From libheif's heif.h
+const HEIF_COLORSPACE_RGB: c_int = 1;
+const HEIF_CHROMA_INTERLEAVED_RGBA: c_int = 11;
+const HEIF_CHANNEL_INTERLEAVED: c_int = 10;
+
+#[repr(C)]
+struct HeifError {
+    code: c_int,
+    subcode: c_int,
+    message: *const c_char,
+}
+
+/// The few libheif functions a cover needs, valid as long as `_library` is loaded
+struct Libheif {
+    context_alloc: unsafe extern "C" fn() -> *mut c_void,
+    context_free: unsafe extern "C" fn(*mut c_void),
+    read_from_memory: unsafe extern "C" fn(*mut c_void, *const c_void, usize, *const c_void) -> HeifError,
+    primary_image_handle: unsafe extern "C" fn(*mut c_void, *mut *mut c_void) -> HeifError,
+    handle_release: unsafe extern "C" fn(*mut c_void),
+    decode_image: unsafe extern "C" fn(*const c_void, *mut *mut c_void, c_int, c_int, *const c_void) -> HeifError,
+    image_release: unsafe extern "C" fn(*mut c_void),
+    image_width: unsafe extern "C" fn(*const c_void, c_int) -> c_int,
+    image_height: unsafe extern "C" fn(*const c_void, c_int) -> c_int,
+    plane: unsafe extern "C" fn(*const c_void, c_int, *mut c_int) -> *const u8,
+    _library: Library,
+}
+
+impl Libheif {
+    fn load() -> Result<Self, String> {
+        // SAFETY: libheif runs no initialisation on load that could clash with this process
+        let library = LIBRARY_NAMES.iter()
+            .find_map(|name| unsafe { Library::new(name) }.ok())
+            .ok_or_else(|| "libheif is not installed".to_string())?;
+        // SAFETY: the signatures are the ones heif.h declares
+        unsafe {
+            let init: unsafe extern "C" fn(*const c_void) -> HeifError = symbol(&library, b"heif_init\0")?;
+            check(init(ptr::null())).map_err(|error| error.to_string())?;
+            Ok(Libheif {
+                context_alloc: symbol(&library, b"heif_context_alloc\0")?,
+                context_free: symbol(&library, b"heif_context_free\0")?,
+                read_from_memory: symbol(&library, b"heif_context_read_from_memory_without_copy\0")?,
+                primary_image_handle: symbol(&library, b"heif_context_get_primary_image_handle\0")?,
+                handle_release: symbol(&library, b"heif_image_handle_release\0")?,
+                decode_image: symbol(&library, b"heif_decode_image\0")?,
+                image_release: symbol(&library, b"heif_image_release\0")?,
+                image_width: symbol(&library, b"heif_image_get_width\0")?,
+                image_height: symbol(&library, b"heif_image_get_height\0")?,
+                plane: symbol(&library, b"heif_image_get_plane_readonly\0")?,
+                _library: library,
+            })
+        }
+    }
+
+    fn global() -> Result<&'static Libheif, ImageError> {
+        static LIBHEIF: OnceLock<Result<Libheif, String>> = OnceLock::new();
+        LIBHEIF.get_or_init(Libheif::load).as_ref().map_err(|reason| {
+            ImageError::Unsupported(UnsupportedError::from_format_and_kind(
+                ImageFormatHint::Exact(ImageFormat::Avif),
+                UnsupportedErrorKind::GenericFeature(reason.clone()),
+            ))
+        })
+    }
+}
+
+/// # Safety
+/// `T` has to be the function pointer type the symbol was declared with
+unsafe fn symbol<T: Copy>(library: &Library, name: &[u8]) -> Result<T, String> {
+    library.get::<T>(name)
+        .map(|symbol| *symbol)
+        .map_err(|error| format!("libheif is too old: {}", error))
+}
+
+/// # Safety
+/// `error` has to come from libheif, with the context it came from still alive for its message
+unsafe fn check(error: HeifError) -> Result<(), ImageError> {
+    if error.code == 0 {
+        return Ok(());
+    }
+    let message = if error.message.is_null() {
+        format!("libheif error {}.{}", error.code, error.subcode)
+    } else {
+        CStr::from_ptr(error.message).to_string_lossy().into_owned()
+    };
+    Err(ImageError::Decoding(DecodingError::new(ImageFormatHint::Exact(ImageFormat::Avif), message)))
+}
+
+/// Releases what libheif allocated, however decoding ends
+struct Owned {
+    pointer: *mut c_void,
+    release: unsafe extern "C" fn(*mut c_void),
+}
+
+impl Owned {
+    fn new(release: unsafe extern "C" fn(*mut c_void)) -> Self {
+        Owned { pointer: ptr::null_mut(), release }
+    }
+}
+
+impl Drop for Owned {
+    fn drop(&mut self) {
+        if !self.pointer.is_null() {
+            // SAFETY: `release` belongs to the function that allocated `pointer`
+            unsafe { (self.release)(self.pointer) };
+        }
+    }
+}
+
+/// Straight RGBA, already turned upright: libheif applies the rotation and mirroring AVIF
+/// stores instead of EXIF
+pub fn decode(bytes: &[u8]) -> Result<RgbaImage, ImageError> {
+    let heif = Libheif::global()?;
+    // SAFETY: every pointer handed to libheif is either null where heif.h allows it or one it
+    // returned itself and that is not released yet, `bytes` outlives the context reading them
+    unsafe {
+        let mut context = Owned::new(heif.context_free);
+        context.pointer = (heif.context_alloc)();
+        if context.pointer.is_null() {
+            return Err(ImageError::Decoding(DecodingError::new(ImageFormatHint::Exact(ImageFormat::Avif), "out of memory")));
+        }
+        check((heif.read_from_memory)(context.pointer, bytes.as_ptr().cast(), bytes.len(), ptr::null()))?;
+
+        let mut handle = Owned::new(heif.handle_release);
+        check((heif.primary_image_handle)(context.pointer, &mut handle.pointer))?;
+
+        let mut image = Owned::new(heif.image_release);
+        check((heif.decode_image)(
+            handle.pointer,
+            &mut image.pointer,
+            HEIF_COLORSPACE_RGB,
+            HEIF_CHROMA_INTERLEAVED_RGBA,
+            ptr::null(),
+        ))?;
+
+        let width = (heif.image_width)(image.pointer, HEIF_CHANNEL_INTERLEAVED);
+        let height = (heif.image_height)(image.pointer, HEIF_CHANNEL_INTERLEAVED);
+        let mut stride: c_int = 0;
+        let plane = (heif.plane)(image.pointer, HEIF_CHANNEL_INTERLEAVED, &mut stride);
+        let row_length = width as usize * 4;
+        if plane.is_null() || width <= 0 || height <= 0 || (stride as usize) < row_length {
+            return Err(ImageError::Decoding(DecodingError::new(ImageFormatHint::Exact(ImageFormat::Avif), "no pixels")));
+        }
+
+        // Rows may be padded, only their start is the image
+        let mut pixels = Vec::with_capacity(row_length * height as usize);
+        for row in 0..height as usize {
+            pixels.extend_from_slice(std::slice::from_raw_parts(plane.add(row * stride as usize), row_length));
+        }
+        Ok(RgbaImage::from_raw(width as u32, height as u32, pixels).expect("rows of the image's width"))
+    }
+}
@@ -0,0 +1,245 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    io::{self, Read, Write},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, OnceLock,
+    },
+};
+
+use slint::SharedPixelBuffer;
+
+use super::Thumbnail;
+
+/// Bump whenever `decode_thumbnail` produces different pixels, so stale files are not reused
+const PIPELINE_VERSION: u32 = 2;
+
+/// Thumbnails kept decoded in memory, 200 tiles of 180×180 are about 26MB
+const MEMORY_CAPACITY: usize = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    pub content_hash: u64,
+    pub size: u32,
+}
+
+impl CacheKey {
+    pub fn new(bytes: &[u8], size: u32) -> Self {
+        CacheKey { content_hash: content_hash(bytes), size }
+    }
+
+    fn file_name(&self) -> String {
+        format!("v{}-{:016x}-{}.rgba", PIPELINE_VERSION, self.content_hash, self.size)
+    }
+}
+
+/// FNV-1a, stable across builds unlike `DefaultHasher`, so file names survive updates
+fn content_hash(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash ^ bytes.len() as u64
+}
+
+#[derive(Default)]
+struct Lru {
+    entries: HashMap<CacheKey, Thumbnail>,
+    // Least recently used first
+    order: VecDeque<CacheKey>,
+}
+
+impl Lru {
+    fn get(&mut self, key: &CacheKey) -> Option<Thumbnail> {
+        let thumbnail = self.entries.get(key)?.clone();
+        self.touch(key);
+        Some(thumbnail)
+    }
+
+    fn insert(&mut self, key: CacheKey, thumbnail: Thumbnail) {
+        if self.entries.insert(key, thumbnail).is_some() {
+            self.touch(&key);
+            return;
+        }
+        self.order.push_back(key);
+        while self.order.len() > MEMORY_CAPACITY {
+            if let Some(evicted) = self.order.pop_front() {
+                self.entries.remove(&evicted);
+            }
+        }
+    }
+
+    fn touch(&mut self, key: &CacheKey) {
+        if let Some(position) = self.order.iter().position(|k| k == key) {
+            self.order.remove(position);
+            self.order.push_back(*key);
+        }
+    }
+}
+
+/// Two level cache for decoded covers: an in-memory LRU in front of raw RGBA files on disk.
+/// Raw pixels are bigger than JPEGs but need no decoding, which is what a Pi is slow at.
+pub struct ThumbnailCache {
+    memory: Mutex<Lru>,
+    directory: Option<PathBuf>,
+}
+
+impl ThumbnailCache {
+    pub fn global() -> &'static ThumbnailCache {
+        static CACHE: OnceLock<ThumbnailCache> = OnceLock::new();
+        CACHE.get_or_init(|| ThumbnailCache::new(crate::xdg::cache_directory().map(|directory| directory.join("thumbnails"))))
+    }
+
+    pub fn new(directory: Option<PathBuf>) -> Self {
+        if let Some(directory) = directory.as_ref() {
+            if let Err(error) = fs::create_dir_all(directory) {
+                eprintln!("Could not create thumbnail cache {}: {}", directory.display(), error);
+            }
+        }
+        ThumbnailCache { memory: Mutex::new(Lru::default()), directory }
+    }
+
+    /// Looks the thumbnail up in memory, then on disk, and only decodes as a last resort
+    pub fn get_or_create<F>(&self, key: CacheKey, create: F) -> Result<Thumbnail, image::ImageError>
+    where
+        F: FnOnce() -> Result<Thumbnail, image::ImageError>,
+    {
+        if let Some(thumbnail) = self.memory.lock().unwrap().get(&key) {
+            return Ok(thumbnail);
+        }
+
+        let thumbnail = match self.read_from_disk(&key) {
+            Some(thumbnail) => thumbnail,
+            None => {
+                let thumbnail = create()?;
+                if let Err(error) = self.write_to_disk(&key, &thumbnail) {
+                    eprintln!("Could not persist thumbnail {}: {}", key.file_name(), error);
+                }
+                thumbnail
+            }
+        };
+
+        self.memory.lock().unwrap().insert(key, thumbnail.clone());
+        Ok(thumbnail)
+    }
+
+    fn read_from_disk(&self, key: &CacheKey) -> Option<Thumbnail> {
+        let path = self.directory.as_ref()?.join(key.file_name());
+        let mut file = fs::File::open(path).ok()?;
+
+        let mut header = [0u8; 8];
+        file.read_exact(&mut header).ok()?;
+        let width = u32::from_le_bytes(header[0..4].try_into().ok()?);
+        let height = u32::from_le_bytes(header[4..8].try_into().ok()?);
+
+        // The header is only trusted as far as the file backs it up, truncated or garbled files
+        // are decoded again
+        let length = (width as u64).checked_mul(height as u64)?.checked_mul(4)?;
+        if file.metadata().ok()?.len() != header.len() as u64 + length {
+            return None;
+        }
+        let mut pixels = vec![0u8; usize::try_from(length).ok()?];
+        file.read_exact(&mut pixels).ok()?;
+
+        Some(SharedPixelBuffer::clone_from_slice(&pixels, width, height))
+    }
+
+    fn write_to_disk(&self, key: &CacheKey, thumbnail: &Thumbnail) -> io::Result<()> {
+        let Some(directory) = self.directory.as_ref() else {
+            return Ok(());
+        };
+
+        // Write to a temporary file first, so readers never see half written thumbnails. Each
+        // writer gets its own, the artwork workers may create the same thumbnail at once.
+        static WRITERS: AtomicU64 = AtomicU64::new(0);
+        let path = directory.join(key.file_name());
+        let temporary_path = directory.join(format!(
+            "{}.{}-{}.tmp",
+            key.file_name(),
+            std::process::id(),
+            WRITERS.fetch_add(1, Ordering::Relaxed)
+        ));
+        let result = fs::File::create(&temporary_path).and_then(|mut file| {
+            file.write_all(&thumbnail.width().to_le_bytes())?;
+            file.write_all(&thumbnail.height().to_le_bytes())?;
+            file.write_all(thumbnail.as_bytes())?;
+            fs::rename(&temporary_path, path)
+        });
+        if result.is_err() {
+            fs::remove_file(&temporary_path).ok();
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{images::TILE_IMAGE_SIZE, test_support};
+
+    fn cache(name: &str) -> (ThumbnailCache, PathBuf) {
+        let directory = test_support::directory().join(name);
+        fs::remove_dir_all(&directory).ok();
+        (ThumbnailCache::new(Some(directory.clone())), directory)
+    }
+
+    /// After a restart the thumbnails the scan pre-generated come from disk, nothing is decoded
+    #[test]
+    fn second_load_reads_only_from_the_cache() {
+        let (scan, directory) = cache("thumbnails-reload");
+        let keys: Vec<_> = (0..200u32).map(|cover| CacheKey::new(&cover.to_le_bytes(), TILE_IMAGE_SIZE)).collect();
+        for (cover, key) in keys.iter().enumerate() {
+            let mut thumbnail = Thumbnail::new(TILE_IMAGE_SIZE, TILE_IMAGE_SIZE);
+            thumbnail.make_mut_bytes().fill(cover as u8);
+            scan.get_or_create(*key, || Ok(thumbnail)).unwrap();
+        }
+
+        // A fresh cache, like after a restart, has nothing in memory but everything on disk
+        let cache = ThumbnailCache::new(Some(directory));
+        for (cover, key) in keys.iter().enumerate() {
+            let thumbnail = cache.get_or_create(*key, || panic!("thumbnail was not on disk")).unwrap();
+            assert_eq!((thumbnail.width(), thumbnail.height()), (TILE_IMAGE_SIZE, TILE_IMAGE_SIZE));
+            assert_eq!(thumbnail.as_bytes()[0], cover as u8);
+        }
+    }
+
+    #[test]
+    fn decodes_again_when_the_header_lies() {
+        let (cache, directory) = cache("thumbnails-header");
+        let key = CacheKey::new(b"cover", 2);
+        let thumbnail = Thumbnail::new(2, 2);
+        cache.write_to_disk(&key, &thumbnail).unwrap();
+        assert!(cache.read_from_disk(&key).is_some());
+
+        for (width, height) in [(u32::MAX, u32::MAX), (0x8000, 0x8000), (2, 3), (1, 2)] {
+            let mut file = width.to_le_bytes().to_vec();
+            file.extend_from_slice(&height.to_le_bytes());
+            file.extend_from_slice(thumbnail.as_bytes());
+            fs::write(directory.join(key.file_name()), file).unwrap();
+            assert!(cache.read_from_disk(&key).is_none(), "{}×{}", width, height);
+        }
+    }
+
+    #[test]
+    fn writers_of_the_same_thumbnail_do_not_collide() {
+        let (cache, directory) = cache("thumbnails-writers");
+        let key = CacheKey::new(b"cover", 64);
+        let thumbnail = Thumbnail::new(64, 64);
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..20 {
+                        cache.write_to_disk(&key, &thumbnail).unwrap();
+                    }
+                });
+            }
+        });
+
+        assert!(cache.read_from_disk(&key).is_some());
+        let files: Vec<_> = fs::read_dir(directory).unwrap().map(|file| file.unwrap().file_name().into_string().unwrap()).collect();
+        assert_eq!(files, vec![key.file_name()]);
+    }
+}
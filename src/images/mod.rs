@@ -0,0 +1,201 @@
+mod artwork;
+mod avif;
+mod cache;
+
+use std::collections::HashMap;
+
+use image::{
+    imageops::FilterType,
+    metadata::Orientation,
+    DynamicImage, ImageDecoder, ImageError, ImageFormat, ImageReader, Rgba, RgbaImage,
+};
+use slint::{Rgba8Pixel, SharedPixelBuffer};
+
+use crate::mock::LibraryEntry;
+
+pub use cache::{CacheKey, ThumbnailCache};
+
+/// Edge length of the round cover shown on a tile
+pub const TILE_IMAGE_SIZE: u32 = 180;
+
+pub type Thumbnail = SharedPixelBuffer<Rgba8Pixel>;
+
+/// Decodes an encoded cover and scales it down to a square thumbnail. Expensive, so keep this
+/// off the UI thread.
+///
+/// Whatever the source colour type (grayscale, 16 bit, with or without alpha) the result is
+/// straight RGBA8, rotated upright according to the EXIF orientation. Supports what the
+/// `image` crate's default formats decode, which includes WebP, and AVIF through libheif.
+pub fn decode_thumbnail(bytes: &[u8], size: u32) -> Result<Thumbnail, ImageError> {
+    let reader = ImageReader::new(std::io::Cursor::new(bytes)).with_guessed_format()?;
+    let img = if reader.format() == Some(ImageFormat::Avif) {
+        DynamicImage::ImageRgba8(avif::decode(bytes)?)
+    } else {
+        let mut decoder = reader.into_decoder()?;
+        let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+        let mut img = DynamicImage::from_decoder(decoder)?;
+        img.apply_orientation(orientation);
+        img
+    };
+    let img = img.resize_to_fill(size, size, FilterType::Triangle).to_rgba8();
+
+    Ok(SharedPixelBuffer::clone_from_slice(img.as_raw(), img.width(), img.height()))
+}
+
+/// Shown instead of covers that can't be decoded: a dark disc with a lighter ring, like a record
+pub fn placeholder_thumbnail(size: u32) -> Thumbnail {
+    let center = size as f32 / 2.0;
+    let img = RgbaImage::from_fn(size, size, |x, y| {
+        let distance = ((x as f32 + 0.5 - center).powi(2) + (y as f32 + 0.5 - center).powi(2)).sqrt() / center;
+        match distance {
+            d if (0.28..0.34).contains(&d) => Rgba([0x55, 0x55, 0x55, 0xff]),
+            d if d < 0.1 => Rgba([0x12, 0x12, 0x12, 0xff]),
+            _ => Rgba([0x2e, 0x2e, 0x2e, 0xff]),
+        }
+    });
+
+    SharedPixelBuffer::clone_from_slice(img.as_raw(), size, size)
+}
+
+/// Cached version of `decode_thumbnail`
+pub fn thumbnail(bytes: &[u8], size: u32) -> Result<Thumbnail, ImageError> {
+    ThumbnailCache::global().get_or_create(CacheKey::new(bytes, size), || decode_thumbnail(bytes, size))
+}
+
+/// What a tile shows for an entry: its cover, a mosaic of its children's covers or generated
+/// artwork, in that order. Broken covers get the placeholder.
+pub fn artwork(entry: &LibraryEntry, size: u32) -> Thumbnail {
+    match entry.image.as_deref() {
+        Some(bytes) => thumbnail(bytes, size).unwrap_or_else(|error| {
+            eprintln!("Could not decode image of library entry {}: {}", entry.id, error);
+            placeholder_thumbnail(size)
+        }),
+        None => artwork::folder_mosaic(entry, size)
+            .unwrap_or_else(|| artwork::generated_artwork(entry, size)),
+    }
+}
+
+/// Looks up the artwork of all entries, spread over all cores
+pub fn artwork_for(entries: &[LibraryEntry], size: u32) -> HashMap<i32, Thumbnail> {
+    if entries.is_empty() {
+        return HashMap::new();
+    }
+
+    let workers = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    let chunk_size = entries.len().div_ceil(workers);
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = entries
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || {
+                chunk.iter()
+                    .map(|entry| (entry.id, artwork(entry, size)))
+                    .collect::<Vec<_>>()
+            }))
+            .collect();
+
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().ok())
+            .flatten()
+            .collect()
+    })
+}
+
+/// Fills the thumbnail cache after a library scan, so browsing never waits for a decode or a
+/// render
+pub fn pregenerate_thumbnails(entries: Vec<LibraryEntry>) {
+    std::thread::spawn(move || {
+        artwork_for(&entries, TILE_IMAGE_SIZE);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, time::Instant};
+
+    use super::*;
+    use crate::{mock, test_support};
+
+    fn fixture(name: &str) -> Vec<u8> {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures").join("images").join(name);
+        std::fs::read(&path).unwrap_or_else(|error| panic!("{}: {}", path.display(), error))
+    }
+
+    fn pixel(thumbnail: &Thumbnail, x: u32, y: u32) -> [u8; 4] {
+        let start = ((y * thumbnail.width() + x) * 4) as usize;
+        thumbnail.as_bytes()[start..start + 4].try_into().unwrap()
+    }
+
+    fn assert_close(actual: [u8; 4], expected: [u8; 4]) {
+        let close = actual.iter().zip(expected).all(|(a, e)| a.abs_diff(e) <= 24);
+        assert!(close, "{:?} is not close to {:?}", actual, expected);
+    }
+
+    #[test]
+    fn spreads_grayscale_over_the_channels() {
+        let thumbnail = decode_thumbnail(&fixture("grayscale.png"), 2).unwrap();
+        assert_eq!(pixel(&thumbnail, 0, 0), [128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn scales_16_bit_channels_down() {
+        let thumbnail = decode_thumbnail(&fixture("16-bit.png"), 2).unwrap();
+        assert_eq!(pixel(&thumbnail, 1, 1), [255, 0, 128, 255]);
+    }
+
+    #[test]
+    fn keeps_alpha_straight() {
+        let thumbnail = decode_thumbnail(&fixture("alpha.png"), 2).unwrap();
+        assert_eq!(pixel(&thumbnail, 0, 1), [10, 20, 30, 128]);
+    }
+
+    /// Stored 16×8 with red on the left and blue on the right, the EXIF orientation turns it a
+    /// quarter clockwise, so red ends up on top
+    #[test]
+    fn turns_upright_by_the_exif_orientation() {
+        let thumbnail = decode_thumbnail(&fixture("orientation-6.jpg"), 16).unwrap();
+        assert_close(pixel(&thumbnail, 2, 2), [255, 0, 0, 255]);
+        assert_close(pixel(&thumbnail, 2, 13), [0, 0, 255, 255]);
+    }
+
+    /// Red on the left and blue on the right, like the JPEG before it is turned
+    #[test]
+    fn decodes_avif() {
+        let thumbnail = decode_thumbnail(&fixture("halves.avif"), 16).unwrap();
+        assert_close(pixel(&thumbnail, 2, 8), [255, 0, 0, 255]);
+        assert_close(pixel(&thumbnail, 13, 8), [0, 0, 255, 255]);
+    }
+
+    /// How long a folder of 200 covers straight from a camera takes, once to decode and once more
+    /// from the cache, which still hashes every cover and looks up the children of every folder
+    /// for its mosaic. Slow without optimisations, run with
+    /// `cargo test --release -- --ignored --nocapture measures_artwork_for`.
+    #[test]
+    #[ignore]
+    fn measures_artwork_for_full_size_covers() {
+        test_support::isolate_directories();
+        let gradient = RgbaImage::from_fn(1500, 1500, |x, y| Rgba([(x / 6) as u8, (y / 6) as u8, ((x + y) / 12) as u8, 255]));
+        let mut cover = vec![];
+        DynamicImage::ImageRgba8(gradient).to_rgb8()
+            .write_to(&mut std::io::Cursor::new(&mut cover), ImageFormat::Jpeg)
+            .unwrap();
+
+        let mut entries = mock::scan_library();
+        for index in 0..200 {
+            let mut entry = LibraryEntry::new_spotify(100_000 + index, "Cover", Some(mock::ROOT_LIBRARY_ENTRY_ID), index);
+            // Decoders stop at the end of the image, what follows only makes each cover unique
+            let mut image = cover.clone();
+            image.extend_from_slice(&index.to_le_bytes());
+            entry.image = Some(image);
+            entries.push(entry);
+        }
+
+        for pass in ["decoded", "cached"] {
+            let started = Instant::now();
+            let artwork = artwork_for(&entries, TILE_IMAGE_SIZE);
+            eprintln!("{} artwork of {} entries in {:?}", pass, artwork.len(), started.elapsed());
+            assert_eq!(artwork.len(), entries.len());
+        }
+    }
+}
@@ -0,0 +1,403 @@
+use std::{collections::BTreeMap, fs, io, path::{Path, PathBuf}};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{mock::{ContentFilter, LibraryEntry, ListeningUsage, ParentalLimits, Progress, SortOrder, Variant, ViewMode}, xdg};
+
+/// What the user changed about an entry, layered over the scanned library on startup
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StoredEntry {
+    pub played_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub view_mode: Option<ViewMode>,
+    pub sort_order: SortOrder,
+    pub favourite: bool,
+}
+
+impl StoredEntry {
+    pub fn from_entry(entry: &LibraryEntry) -> Self {
+        StoredEntry {
+            played_at: entry.played_at,
+            view_mode: entry.view_mode,
+            sort_order: entry.sort_order,
+            favourite: entry.favourite,
+        }
+    }
+
+    pub fn apply(&self, entry: &mut LibraryEntry) {
+        entry.played_at = self.played_at;
+        entry.view_mode = self.view_mode;
+        entry.sort_order = self.sort_order;
+        entry.favourite = self.favourite;
+    }
+}
+
+/// Structure the parents changed by hand. Complete, so entries created that way can be rebuilt
+/// without a scan.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EntryEdit {
+    pub variant: Variant,
+    pub name: String,
+    pub parent_id: Option<i32>,
+    pub sort_key: i32,
+    /// File names in the media directory
+    #[serde(default)]
+    pub cover: Option<String>,
+    #[serde(default)]
+    pub audio: Option<String>,
+    /// Missing in stores written before entries had labels, the scanned ones stay then
+    #[serde(default)]
+    pub labels: Option<EntryLabels>,
+}
+
+/// What content filters look at
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntryLabels {
+    pub genre: Option<String>,
+    pub age_rating: Option<u8>,
+    pub tags: Vec<String>,
+}
+
+impl EntryLabels {
+    pub fn from_entry(entry: &LibraryEntry) -> Self {
+        EntryLabels {
+            genre: entry.genre.clone(),
+            age_rating: entry.age_rating,
+            tags: entry.tags.clone(),
+        }
+    }
+
+    pub fn apply(&self, entry: &mut LibraryEntry) {
+        entry.genre = self.genre.clone();
+        entry.age_rating = self.age_rating;
+        entry.tags = self.tags.clone();
+    }
+}
+
+impl EntryEdit {
+    pub fn apply(&self, entry: &mut LibraryEntry) {
+        entry.variant = self.variant.clone();
+        entry.name = self.name.clone();
+        entry.parent_id = self.parent_id;
+        entry.sort_key = self.sort_key;
+        if let Some(labels) = &self.labels {
+            labels.apply(entry);
+        }
+        if let Some(cover) = self.cover.as_deref() {
+            entry.image = media_path(cover).and_then(|path| fs::read(path).ok());
+        }
+        if let Some(audio) = self.audio.as_deref() {
+            entry.track_source = media_path(audio).map(|path| LibraryEntry::file_track_source(entry.id, &entry.name, &path));
+        }
+    }
+}
+
+/// The library's structure as the parents changed it, shared by every profile and kept as JSON
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LibraryStore {
+    /// Created, renamed, moved or reordered entries, keyed by library entry id
+    pub edits: BTreeMap<i32, EntryEdit>,
+    /// Scanned entries the parents deleted, so the next scan does not bring them back
+    pub deleted: Vec<i32>,
+}
+
+impl LibraryStore {
+    /// A missing or unreadable store starts empty, losing edits is no reason not to start
+    pub fn load() -> Self {
+        store_path().and_then(|path| read_json(&path)).unwrap_or_default()
+    }
+
+    /// Records the structure of an entry, keeping the media it already has
+    pub fn edit(&mut self, entry: &LibraryEntry) {
+        let previous = self.edits.remove(&entry.id);
+        self.edits.insert(entry.id, EntryEdit {
+            variant: entry.variant.clone(),
+            name: entry.name.clone(),
+            parent_id: entry.parent_id,
+            sort_key: entry.sort_key,
+            cover: previous.as_ref().and_then(|edit| edit.cover.clone()),
+            audio: previous.and_then(|edit| edit.audio),
+            labels: Some(EntryLabels::from_entry(entry)),
+        });
+    }
+
+    /// Drops everything known about a deleted entry, its media files go with the next `prune_media`
+    pub fn forget(&mut self, id: i32) {
+        self.edits.remove(&id);
+        if !self.deleted.contains(&id) {
+            self.deleted.push(id);
+        }
+    }
+
+    /// Removes media files no entry refers to anymore. Only safe at startup, before anything
+    /// could be undone.
+    pub fn prune_media(&self) {
+        let Some(Ok(files)) = media_directory().map(fs::read_dir) else {
+            return;
+        };
+        let used: Vec<&str> = self.edits.values()
+            .flat_map(|edit| [edit.cover.as_deref(), edit.audio.as_deref()])
+            .flatten()
+            .collect();
+        for file in files.flatten() {
+            if !file.file_name().to_str().is_some_and(|name| used.contains(&name)) {
+                fs::remove_file(file.path()).ok();
+            }
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        write_json(&store_path().ok_or_else(no_data_directory)?, self)
+    }
+}
+
+/// What one profile made of the library and how it listens, in a file of its own
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileStore {
+    /// Keyed by library entry id, entries nobody touched are left out
+    pub entries: BTreeMap<i32, StoredEntry>,
+    /// Ids shown on the home screen, in the order they were pinned
+    pub pinned: Vec<i32>,
+    pub limits: ParentalLimits,
+    pub filter: ContentFilter,
+    pub usage: ListeningUsage,
+    pub session: Session,
+}
+
+/// Where listening left off, so the next start or switch back picks up from there
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// Keyed by library entry id
+    pub resume_positions: BTreeMap<i32, Progress>,
+    /// Between 0 and 1, `None` keeps the player's default
+    pub volume: Option<f32>,
+}
+
+impl ProfileStore {
+    /// `None` is the profile used when no profiles are set up. Like the library store, a missing
+    /// or broken file starts empty.
+    pub fn load(profile_id: Option<&str>) -> Self {
+        let Some(path) = profile_path(profile_id) else {
+            return ProfileStore::default();
+        };
+        if let Some(store) = read_json(&path) {
+            return store;
+        }
+        if profile_id.is_some() {
+            return ProfileStore::default();
+        }
+        // Before there were profiles, this all lived in the library store
+        let Some(store) = store_path().and_then(|path| read_json::<ProfileStore>(&path)) else {
+            return ProfileStore::default();
+        };
+        if let Err(error) = store.save(None) {
+            eprintln!("Could not move user data out of the library store: {}", error);
+        }
+        store
+    }
+
+    /// Records the user data of an entry, dropping it again once it is back to defaults
+    pub fn update(&mut self, entry: &LibraryEntry) {
+        let stored = StoredEntry::from_entry(entry);
+        if stored == StoredEntry::default() {
+            self.entries.remove(&entry.id);
+        } else {
+            self.entries.insert(entry.id, stored);
+        }
+    }
+
+    pub fn forget(&mut self, id: i32) {
+        self.entries.remove(&id);
+        self.pinned.retain(|pinned| *pinned != id);
+        self.session.resume_positions.remove(&id);
+    }
+
+    pub fn save(&self, profile_id: Option<&str>) -> io::Result<()> {
+        write_json(&profile_path(profile_id).ok_or_else(no_data_directory)?, self)
+    }
+}
+
+/// Podcasts the parents subscribed to, shared by every profile like the library's structure
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PodcastStore {
+    /// Keyed by the podcast's library entry id
+    pub subscriptions: BTreeMap<i32, Subscription>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Subscription {
+    pub url: String,
+    /// Newest first
+    pub episodes: Vec<StoredEpisode>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StoredEpisode {
+    /// Library entry id, handed out once and kept for as long as the feed lists the episode
+    pub id: i32,
+    /// Tells episodes apart across refreshes, titles and URLs may change
+    pub guid: String,
+    pub title: String,
+    /// The enclosure
+    pub url: String,
+    pub published: Option<chrono::DateTime<chrono::Utc>>,
+    /// Bytes the feed announced, 0 when it did not say
+    pub length: u64,
+    /// At least this many bytes, learned from a download that outgrew the quota. Feeds announce
+    /// wrong sizes or none at all.
+    pub known_length: u64,
+    /// File name in the downloads directory
+    pub download: Option<String>,
+    /// Somebody listened to the end, the download is deleted then
+    pub played: bool,
+}
+
+impl StoredEpisode {
+    /// What the download will take at least
+    pub fn size(&self) -> u64 {
+        self.length.max(self.known_length)
+    }
+}
+
+impl PodcastStore {
+    pub fn load() -> Self {
+        podcast_store_path().and_then(|path| read_json(&path)).unwrap_or_default()
+    }
+
+    /// Drops subscriptions whose podcast is gone and the downloads of deleted episodes. Deleted
+    /// episodes stay known, so the next refresh does not bring them back. Only safe at startup,
+    /// before anything could be brought back by undoing.
+    pub fn prune(&mut self, exists: impl Fn(i32) -> bool) {
+        self.subscriptions.retain(|id, _| exists(*id));
+        for episode in self.subscriptions.values_mut().flat_map(|subscription| subscription.episodes.iter_mut()) {
+            if !exists(episode.id) {
+                episode.download = None;
+            }
+        }
+        let Some(Ok(files)) = download_directory().map(fs::read_dir) else {
+            return;
+        };
+        let used: Vec<&str> = self.subscriptions.values()
+            .flat_map(|subscription| subscription.episodes.iter())
+            .filter_map(|episode| episode.download.as_deref())
+            .collect();
+        for file in files.flatten() {
+            if !file.file_name().to_str().is_some_and(|name| used.contains(&name)) {
+                fs::remove_file(file.path()).ok();
+            }
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        write_json(&podcast_store_path().ok_or_else(no_data_directory)?, self)
+    }
+}
+
+/// `None` when the file is missing or broken, which is logged unless it is simply not there yet
+fn read_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    match fs::read(path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).map_err(|error| {
+            eprintln!("Ignoring broken store {}: {}", path.display(), error);
+        }).ok(),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+        Err(error) => {
+            eprintln!("Could not read store {}: {}", path.display(), error);
+            None
+        }
+    }
+}
+
+fn write_json(path: &Path, value: &impl Serialize) -> io::Result<()> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    // Write next to the store and rename, a power cut must not leave half a file behind
+    let temporary_path = path.with_extension("tmp");
+    fs::write(&temporary_path, serde_json::to_vec_pretty(value)?)?;
+    fs::rename(temporary_path, path)
+}
+
+fn no_data_directory() -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, "no data directory")
+}
+
+/// Stores uploaded audio or covers under a new name and returns that name
+pub fn save_media(bytes: &[u8], extension: &str) -> io::Result<String> {
+    let directory = media_directory().ok_or_else(no_data_directory)?;
+    fs::create_dir_all(&directory)?;
+    let stamp = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    let name = format!("{:x}.{}", stamp, extension);
+    fs::write(directory.join(&name), bytes)?;
+    Ok(name)
+}
+
+/// Where a downloaded episode is kept, `name` is what `StoredEpisode::download` holds
+pub fn download_path(name: &str) -> Option<PathBuf> {
+    Some(download_directory()?.join(file_name(name)?))
+}
+
+/// Deleting is best effort, whatever is left over goes with the next start
+pub fn remove_download(name: &str) {
+    if let Some(path) = download_path(name) {
+        fs::remove_file(path).ok();
+    }
+}
+
+/// Bytes taken by downloaded episodes, what the storage quota is checked against
+pub fn downloads_size() -> u64 {
+    let Some(Ok(files)) = download_directory().map(fs::read_dir) else {
+        return 0;
+    };
+    files.flatten()
+        .filter_map(|file| file.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+pub fn media_path(name: &str) -> Option<PathBuf> {
+    // Names come from the store, which could have been edited by hand
+    Some(media_directory()?.join(file_name(name)?))
+}
+
+/// Profile ids come from the config, they must not point outside the profiles directory
+pub fn is_valid_profile_id(id: &str) -> bool {
+    !id.is_empty() && file_name(id).is_some()
+}
+
+fn file_name(name: &str) -> Option<&str> {
+    if name.contains(['/', '\\']) || name.starts_with('.') {
+        return None;
+    }
+    Some(name)
+}
+
+fn store_path() -> Option<PathBuf> {
+    Some(xdg::data_directory()?.join("library.json"))
+}
+
+fn profile_path(profile_id: Option<&str>) -> Option<PathBuf> {
+    match profile_id {
+        Some(id) => Some(xdg::data_directory()?.join("profiles").join(format!("{}.json", file_name(id)?))),
+        None => Some(xdg::data_directory()?.join("profile.json")),
+    }
+}
+
+fn media_directory() -> Option<PathBuf> {
+    Some(xdg::data_directory()?.join("media"))
+}
+
+fn podcast_store_path() -> Option<PathBuf> {
+    Some(xdg::data_directory()?.join("podcasts.json"))
+}
+
+fn download_directory() -> Option<PathBuf> {
+    Some(xdg::data_directory()?.join("downloads"))
+}
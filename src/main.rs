@@ -1,80 +1,83 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::error::Error;
-
-use image::{GenericImageView, ImageReader};
-use slint::{Image, Model, ModelRc, SharedPixelBuffer};
+use std::{error::Error, path::PathBuf};
 
 use crate::mock::PlayerEvent;
 
 slint::include_modules!();
 
 // mod controllers;
+mod config;
+mod headless;
+mod images;
+mod library_store;
 mod model;
 mod mock;
+mod podcasts;
+mod remote;
+mod search_index;
+#[cfg(test)]
+mod test_support;
 mod view_model;
+mod xdg;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let ui = AppWindow::new()?;
+    let args: Vec<String> = std::env::args().collect();
+    // --config <path> instead of the one in the user's config directory
+    let config_path = args.iter().position(|arg| arg == "--config").and_then(|position| args.get(position + 1)).map(PathBuf::from);
+    let config = config::Config::load(config_path.as_deref())?;
+
     let player = mock::Player::new();
-    let state = model::State::new(player);
+    let state = model::State::new(player.clone(), config.pin, config.profiles);
 
     {
         let state = state.clone();
         player.subscribe(move |event| {
             match event {
-                PlayerEvent::Playing(library_entry) => state.dispatch(id)
+                PlayerEvent::PositionChanged(progress) => state.dispatch(model::actions::Action::SetProgress(progress)),
+                PlayerEvent::Playing(_) | PlayerEvent::Resumed => state.dispatch(model::actions::Action::SetIsPlaying(true)),
+                PlayerEvent::Paused | PlayerEvent::Stopped => state.dispatch(model::actions::Action::SetIsPlaying(false)),
+                PlayerEvent::Finished => state.dispatch(model::actions::Action::TrackFinished),
             }
-        });
+        })?;
+    }
+
+    let podcasts = podcasts::Podcasts::new(&config.podcasts);
+    podcasts.spawn(state.clone());
+
+    if let Some(remote) = config.remote {
+        remote::serve(state.clone(), podcasts.clone(), remote);
+    }
+
+    // --headless [script]: no window, commands from the script or stdin
+    if let Some(position) = args.iter().position(|arg| arg == "--headless") {
+        let script = args.get(position + 1).filter(|arg| !arg.starts_with("--")).map(PathBuf::from);
+        state.dispatch(model::actions::Action::LoadLibraryEntry(mock::ROOT_LIBRARY_ENTRY_ID));
+        tokio::task::spawn_blocking(move || headless::run(state, podcasts, script)).await??;
+        return Ok(());
     }
+
+    let ui = AppWindow::new()?;
     
     // Erstelle ContentVM - UI muss stark referenziert bleiben
     let _content_vm = view_model::content::ContentVM::new(ui.as_weak(), state.clone());
     let _messages_vm = view_model::messages::MessagesVM::new(ui.as_weak(), state.clone());
     let _navbar_vm = view_model::navbar::NavbarVM::new(ui.as_weak(), state.clone());
+    let _search_vm = view_model::search::SearchVM::new(ui.as_weak(), state.clone());
+    let _edit_vm = view_model::edit::EditVM::new(ui.as_weak(), state.clone());
+    let _pin_pad_vm = view_model::pin_pad::PinPadVM::new(ui.as_weak(), state.clone());
+    let _child_lock_vm = view_model::child_lock::ChildLockVM::new(ui.as_weak(), state.clone());
+    let _time_up_vm = view_model::time_up::TimeUpVM::new(ui.as_weak(), state.clone());
+    let _profiles_vm = view_model::profiles::ProfilesVM::new(ui.as_weak(), state.clone());
     
     // Zugriff auf Content Global
     let _content_global = ui.global::<Content>();
     
-    // Lade Test-Daten
-    state.dispatch(model::actions::Action::LoadLibraryEntry(0));
-
-    // let controller = controllers::ContentController::new(ui.clone(), state);
+    images::pregenerate_thumbnails(mock::scan_library());
 
-    ui.global::<Helper>().on_get_image(|image: ModelRc<i32>| {
-        let cb = move || {
-            let data_i32 = image.iter().collect::<Vec<i32>>();
-            let data_u8: Vec<u8> = data_i32.iter().map(|&v| v as u8).collect();
-            let img = ImageReader::new(std::io::Cursor::new(data_u8))
-                .with_guessed_format()
-                .map_err(|e| slint::platform::PlatformError::from(format!("{:?}", e)))?
-                .decode()
-                .map_err(|e| slint::platform::PlatformError::from(format!("{:?}", e)))?;
-
-            let (width, height) = img.dimensions();
-
-            Ok(Image::from_rgb8(SharedPixelBuffer::clone_from_slice(
-                img.as_bytes(),
-                width,
-                height,
-            )))
-        };
-        cb().unwrap_or_else(|_err: slint::platform::PlatformError| Image::default())
-    });
-
-    // let img = ImageReader::new(std::io::Cursor::new(decoded_bytes))
-    //     .with_guessed_format()?
-    //     .decode()?;
-    //
-    // // let (width, height) = img.dimensions();
-    //
-    // let image_data = slint::Image::from_rgb8(SharedPixelBuffer::clone_from_slice(
-    //     img.as_bytes(),
-    //     180,
-    //     180,
-    // ));
-    // ui.set_image_url(image_data);
+    // Lade Test-Daten
+    state.dispatch(model::actions::Action::LoadLibraryEntry(mock::ROOT_LIBRARY_ENTRY_ID));
 
     ui.run()?;
 
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use crate::library_store::{self, EntryEdit, EntryLabels};
+
+use super::{
+    entry::load_image,
+    library::{entry_name, library, natural_cmp, resolve_ancestors, Library},
+    LibraryEntry, LibraryError,
+};
+
+/// Creates an empty folder at the end of its parent and returns its id
+pub fn create_folder(parent_id: i32, name: &str) -> Result<i32, LibraryError> {
+    let mut library = library().lock().unwrap();
+    library.folder(parent_id)?;
+    let (id, sort_key) = (library.next_id(), library.next_sort_key(parent_id));
+    library.add(LibraryEntry::new_folder(id, &entry_name(name)?, Some(parent_id), "".to_string(), sort_key));
+    library.save();
+    Ok(id)
+}
+
+/// Stores uploaded audio as a new track at the end of its parent and returns its id
+pub fn create_track(parent_id: i32, name: &str, audio: &[u8], extension: &str) -> Result<i32, LibraryError> {
+    let name = entry_name(name)?;
+    if extension.is_empty() || extension.len() > 5 || !extension.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(LibraryError::Invalid(format!("unknown audio format {:?}", extension)));
+    }
+    let mut library = library().lock().unwrap();
+    library.folder(parent_id)?;
+
+    let file_name = library_store::save_media(audio, &extension.to_lowercase()).map_err(LibraryError::from)?;
+    let path = library_store::media_path(&file_name).ok_or_else(|| LibraryError::Network("no data directory".to_string()))?;
+    let (id, sort_key) = (library.next_id(), library.next_sort_key(parent_id));
+    library.add(LibraryEntry::new_file(id, &name, Some(parent_id), sort_key, &path));
+    if let Some(edit) = library.store.edits.get_mut(&id) {
+        edit.audio = Some(file_name);
+    }
+    library.save();
+    Ok(id)
+}
+
+pub fn rename_entry(id: i32, name: &str) -> Result<(), LibraryError> {
+    let name = entry_name(name)?;
+    let mut library = library().lock().unwrap();
+    library.editable(id)?;
+    library.edit(&[id], |entry| entry.name = name.clone())
+}
+
+/// Moves the entry to the end of another folder, never into itself or one of its own folders
+pub fn move_entry(id: i32, parent_id: i32) -> Result<(), LibraryError> {
+    let mut library = library().lock().unwrap();
+    let entry = library.editable(id)?;
+    if entry.parent_id == Some(parent_id) {
+        return Ok(());
+    }
+    let parent = library.folder(parent_id)?;
+    if parent.id == id || resolve_ancestors(&library.entries, parent).iter().any(|crumb| crumb.id == id) {
+        return Err(LibraryError::Invalid(format!("{} cannot be moved into itself", entry.name)));
+    }
+    let sort_key = library.next_sort_key(parent_id);
+    library.edit(&[id], |entry| {
+        entry.parent_id = Some(parent_id);
+        entry.sort_key = sort_key;
+    })
+}
+
+/// Puts the listed children first, in the given order, the rest keep their order behind them.
+/// Only shows in folders sorted by hand.
+pub fn reorder_children(parent_id: i32, ids: &[i32]) -> Result<(), LibraryError> {
+    let mut library = library().lock().unwrap();
+    library.folder(parent_id)?;
+    let mut children: Vec<&LibraryEntry> = library.entries.iter().filter(|e| e.parent_id == Some(parent_id)).collect();
+    if let Some(stranger) = ids.iter().find(|id| !children.iter().any(|child| child.id == **id)) {
+        return Err(LibraryError::Invalid(format!("{} is not in folder {}", stranger, parent_id)));
+    }
+    children.sort_by_key(|child| child.sort_key);
+    let order: Vec<i32> = ids.iter().copied()
+        .chain(children.iter().map(|child| child.id).filter(|id| !ids.contains(id)))
+        .collect();
+    let sort_keys: HashMap<i32, i32> = order.iter().enumerate().map(|(sort_key, id)| (*id, sort_key as i32)).collect();
+    // One edit for all of them, the store is written once
+    library.edit(&order, |entry| entry.sort_key = sort_keys[&entry.id])
+}
+
+/// Deletes the entry with everything inside it
+pub fn delete_entry(id: i32) -> Result<(), LibraryError> {
+    let mut library = library().lock().unwrap();
+    library.editable(id)?;
+    let doomed = library.subtree(id);
+    library.entries.retain(|entry| !doomed.contains(&entry.id));
+    for id in doomed {
+        library.store.forget(id);
+        library.user.forget(id);
+    }
+    library.save();
+    Ok(())
+}
+
+/// Genre and tags are trimmed, empty ones dropped. Ratings go up to 18 like the FSK's.
+pub fn set_labels(id: i32, labels: EntryLabels) -> Result<(), LibraryError> {
+    if let Some(rating) = labels.age_rating.filter(|rating| *rating > 18) {
+        return Err(LibraryError::Invalid(format!("age rating {} is above 18", rating)));
+    }
+    let genre = labels.genre.map(|genre| genre.trim().to_string()).filter(|genre| !genre.is_empty());
+    let mut tags: Vec<String> = Vec::new();
+    for tag in labels.tags.iter().map(|tag| tag.trim()).filter(|tag| !tag.is_empty()) {
+        if !tags.iter().any(|known| known.to_lowercase() == tag.to_lowercase()) {
+            tags.push(tag.to_string());
+        }
+    }
+    let labels = EntryLabels { genre, age_rating: labels.age_rating, tags };
+    let mut library = library().lock().unwrap();
+    library.editable(id)?;
+    library.edit(&[id], |entry| labels.apply(entry))
+}
+
+/// Replaces the cover with one of the images shipped in the images folder
+pub fn pick_cover(id: i32, name: &str) -> Result<(), LibraryError> {
+    let image = cover_image(name).ok_or_else(|| LibraryError::Invalid(format!("no cover named {}", name)))?;
+    set_cover(id, image)
+}
+
+/// Only plain file names, nothing outside the images folder
+pub fn cover_image(name: &str) -> Option<Vec<u8>> {
+    is_cover_name(name).then(|| load_image(name.to_string())).flatten()
+}
+
+/// A file right inside the images folder, hidden ones like `.DS_Store` are no covers
+fn is_cover_name(name: &str) -> bool {
+    !name.starts_with('.') && std::path::Path::new(name).file_name() == Some(std::ffi::OsStr::new(name))
+}
+
+/// File names in the images folder, covers can be picked from these on the device
+pub fn cover_images() -> Vec<String> {
+    let Ok(directory) = std::env::current_dir().and_then(|dir| std::fs::read_dir(dir.join("images"))) else {
+        return vec![];
+    };
+    let mut names: Vec<String> = directory
+        .filter_map(|file| file.ok()?.file_name().into_string().ok())
+        .filter(|name| is_cover_name(name))
+        .collect();
+    names.sort_by(|a, b| natural_cmp(a, b));
+    names
+}
+
+/// Replaces the cover, anything the image decoder can decode is accepted
+pub fn set_cover(id: i32, image: Vec<u8>) -> Result<(), LibraryError> {
+    let format = image::guess_format(&image)
+        .map_err(|error| LibraryError::Invalid(format!("not an image: {}", error)))?;
+    // Refused right away rather than stored and shown as a broken cover
+    crate::images::decode_thumbnail(&image, crate::images::TILE_IMAGE_SIZE)
+        .map_err(|error| LibraryError::Decode { id, reason: error.to_string() })?;
+    let extension = format.extensions_str().first().copied().unwrap_or("img");
+    let mut library = library().lock().unwrap();
+    library.entry(id)?;
+
+    let file_name = library_store::save_media(&image, extension).map_err(LibraryError::from)?;
+    library.edit(&[id], |entry| entry.image = Some(image.clone()))?;
+    if let Some(edit) = library.store.edits.get_mut(&id) {
+        // The previous file stays until the next start, undoing may still need it
+        edit.cover = Some(file_name);
+    }
+    library.save();
+    Ok(())
+}
+
+/// An entry as it was before an edit, `restore_entries` puts it back
+#[derive(Clone, Debug)]
+pub struct EntryBackup {
+    entry: LibraryEntry,
+    edit: Option<EntryEdit>,
+}
+
+impl EntryBackup {
+    fn of(library: &Library, entry: &LibraryEntry) -> Self {
+        EntryBackup { entry: entry.clone(), edit: library.store.edits.get(&entry.id).cloned() }
+    }
+}
+
+pub fn backup_entries(ids: &[i32]) -> Result<Vec<EntryBackup>, LibraryError> {
+    let library = library().lock().unwrap();
+    ids.iter().map(|id| Ok(EntryBackup::of(&library, library.entry(*id)?))).collect()
+}
+
+/// The entry with everything inside it, what deleting it takes away
+pub fn backup_subtree(id: i32) -> Result<Vec<EntryBackup>, LibraryError> {
+    let library = library().lock().unwrap();
+    library.entry(id)?;
+    Ok(library.subtree(id).into_iter()
+        .filter_map(|id| library.entries.iter().find(|e| e.id == id))
+        .map(|entry| EntryBackup::of(&library, entry))
+        .collect())
+}
+
+/// Puts the structure of backed up entries back, deleted ones come back whole. What happened to
+/// them in the meantime, like being played or liked, is kept.
+pub fn restore_entries(backups: &[EntryBackup]) -> Result<(), LibraryError> {
+    let mut library = library().lock().unwrap();
+    for backup in backups {
+        let id = backup.entry.id;
+        library.store.deleted.retain(|deleted| *deleted != id);
+        match &backup.edit {
+            Some(edit) => library.store.edits.insert(id, edit.clone()),
+            None => library.store.edits.remove(&id),
+        };
+        let entry = match library.entries.iter().position(|e| e.id == id) {
+            Some(position) => {
+                let entry = &mut library.entries[position];
+                let mut restored = backup.entry.clone();
+                restored.played_at = entry.played_at;
+                restored.favourite = entry.favourite;
+                restored.view_mode = entry.view_mode;
+                restored.pinned = entry.pinned;
+                *entry = restored;
+                entry.clone()
+            }
+            None => {
+                library.entries.push(backup.entry.clone());
+                if backup.entry.pinned {
+                    library.user.pinned.push(id);
+                }
+                backup.entry.clone()
+            }
+        };
+        library.user.update(&entry);
+    }
+    library.save();
+    Ok(())
+}
@@ -0,0 +1,292 @@
+use std::fmt::Debug;
+
+use serde::{Deserialize, Serialize};
+
+use crate::library_store::{self, StoredEpisode};
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Variant {
+    Folder,
+    Stream,
+    File,
+    Spotify,
+    /// Subscribed feed, its episodes are its children
+    Podcast,
+    Episode,
+} 
+
+impl Variant {
+    /// Opens to show its children instead of playing
+    pub fn opens(&self) -> bool {
+        matches!(self, Variant::Folder | Variant::Podcast)
+    }
+}
+
+impl Debug for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Variant::Folder => write!(f, "folder"),
+            Variant::Stream => write!(f, "stream"),
+            Variant::File => write!(f, "file"),
+            Variant::Spotify => write!(f, "spotify"),
+            Variant::Podcast => write!(f, "podcast"),
+            Variant::Episode => write!(f, "episode"),
+        }
+    }
+}
+
+/// How a folder shows its children, folders without one decide by the variants of their children
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ViewMode {
+    Tiles,
+    List,
+}
+
+/// Order of a folder's children, ties are always broken by `sort_key` and then by name
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortOrder {
+    /// By `sort_key`, the order the library was arranged in
+    #[default]
+    Manual,
+    Name,
+    /// Most recently played first, never played ones last
+    RecentlyPlayed,
+    /// Newest first
+    DateAdded,
+}
+
+#[derive(Clone, Debug)]
+pub struct TrackSource {
+    pub id: i32,
+    pub library_entry_id: Option<i32>,
+    pub title: String,
+    pub url: Option<String>,
+    pub file: Option<Vec<u8>>,
+    pub spotify_id: Option<String>,
+    pub spotify_type: Option<String>,
+    /// Downloaded copy of `url`, played instead while it is there
+    pub local_path: Option<std::path::PathBuf>,
+}
+
+impl TrackSource {
+    /// Where the player finds the audio, `None` if the source carries it in `file`
+    pub fn location(&self) -> Option<String> {
+        if let Some(path) = &self.local_path {
+            return Some(format!("file://{}", path.display()));
+        }
+        if let Some(url) = &self.url {
+            return Some(url.clone());
+        }
+        match (&self.spotify_type, &self.spotify_id) {
+            (Some(kind), Some(id)) => Some(format!("spotify:{}:{}", kind, id)),
+            _ => None,
+        }
+    }
+}
+
+/// Lightweight reference to an ancestor, enough to render and navigate a breadcrumb
+#[derive(Clone, Debug)]
+pub struct Breadcrumb {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct LibraryEntry {
+    pub id: i32,
+    pub parent_id: Option<i32>,
+    pub variant: Variant,
+    pub name: String,
+    pub image: Option<Vec<u8>>,
+    pub played_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub added_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub sort_key: i32,
+    pub view_mode: Option<ViewMode>,
+    /// How this folder orders its children
+    pub sort_order: SortOrder,
+    pub favourite: bool,
+    /// Like "Hörspiel" or "Musik", searched along with the name
+    pub genre: Option<String>,
+    /// Minimum age as printed on the cover, like FSK 0, 6, 12, 16 or 18
+    pub age_rating: Option<u8>,
+    /// Free-form metadata such as the series, searched along with the name
+    pub tags: Vec<String>,
+    /// Shown on the home screen ahead of the root's own children
+    pub pinned: bool,
+    /// A podcast episode nobody listened to the end yet
+    pub unplayed: bool,
+    pub children: Option<Vec<LibraryEntry>>, // Just used to pass children from API to client
+    pub track_source: Option<TrackSource>, // Just used to pass children from API to client
+    pub ancestors: Option<Vec<Breadcrumb>>, // Just used to pass the path from root to parent from API to client
+    // Only relevant for the user interface
+    pub parent_name: Option<String>,
+    // Only relevant for the user interface
+    pub parent_image: Option<Vec<u8>>,
+}
+
+impl LibraryEntry {
+    pub fn new_folder(id: i32, name: &str, parent_id: Option<i32>, image: String, sort_key: i32) -> Self {
+        LibraryEntry {
+            id,
+            parent_id,
+            variant: Variant::Folder,
+            name: name.to_string(),
+            image: load_image(image),
+            played_at: None,
+            added_at: None,
+            sort_key,
+            view_mode: None,
+            sort_order: SortOrder::default(),
+            favourite: false,
+            genre: None,
+            age_rating: None,
+            tags: vec![],
+            pinned: false,
+            unplayed: false,
+            children: Some(vec![]),
+            track_source: None,
+            ancestors: None,
+            parent_name: None,
+            parent_image: None,
+        }
+    }
+
+    pub fn new_spotify(
+        id: i32,
+        name: &str,
+        parent_id: Option<i32>,
+        sort_key: i32,
+    ) -> Self {
+        LibraryEntry {
+            id,
+            parent_id,
+            variant: Variant::Spotify,
+            name: name.to_string(),
+            image: None,
+            played_at: None,
+            added_at: None,
+            sort_key,
+            view_mode: None,
+            sort_order: SortOrder::default(),
+            favourite: false,
+            genre: None,
+            age_rating: None,
+            tags: vec![],
+            pinned: false,
+            unplayed: false,
+            children: None,
+            track_source: Some(TrackSource {
+                id: 1,
+                library_entry_id: None,
+                title: name.to_string(),
+                url: None,
+                file: None,
+                spotify_id: Some("some_id".to_string()),
+                spotify_type: Some("track".to_string()),
+                local_path: None,
+            }),
+            ancestors: None,
+            parent_name: None,
+            parent_image: None,
+        }
+    }
+
+    /// A track stored on the device, `path` is where its audio lives
+    pub fn new_file(id: i32, name: &str, parent_id: Option<i32>, sort_key: i32, path: &std::path::Path) -> Self {
+        LibraryEntry {
+            id,
+            parent_id,
+            variant: Variant::File,
+            name: name.to_string(),
+            image: None,
+            played_at: None,
+            added_at: None,
+            sort_key,
+            view_mode: None,
+            sort_order: SortOrder::default(),
+            favourite: false,
+            genre: None,
+            age_rating: None,
+            tags: vec![],
+            pinned: false,
+            unplayed: false,
+            children: None,
+            track_source: Some(Self::file_track_source(id, name, path)),
+            ancestors: None,
+            parent_name: None,
+            parent_image: None,
+        }
+    }
+
+    /// Opens like a folder, episodes are added by refreshing the feed
+    pub fn new_podcast(id: i32, name: &str, parent_id: Option<i32>, sort_key: i32) -> Self {
+        let mut podcast = Self::new_folder(id, name, parent_id, "".to_string(), sort_key);
+        podcast.variant = Variant::Podcast;
+        podcast
+    }
+
+    /// Streams from the enclosure until the download is there
+    pub fn new_episode(podcast_id: i32, episode: &StoredEpisode, sort_key: i32) -> Self {
+        LibraryEntry {
+            id: episode.id,
+            parent_id: Some(podcast_id),
+            variant: Variant::Episode,
+            name: episode.title.clone(),
+            image: None,
+            played_at: None,
+            added_at: episode.published,
+            sort_key,
+            view_mode: None,
+            sort_order: SortOrder::default(),
+            favourite: false,
+            genre: None,
+            age_rating: None,
+            tags: vec![],
+            pinned: false,
+            unplayed: !episode.played,
+            children: None,
+            track_source: Some(TrackSource {
+                id: episode.id,
+                library_entry_id: Some(episode.id),
+                title: episode.title.clone(),
+                url: Some(episode.url.clone()),
+                file: None,
+                spotify_id: None,
+                spotify_type: None,
+                local_path: episode.download.as_deref().and_then(library_store::download_path),
+            }),
+            ancestors: None,
+            parent_name: None,
+            parent_image: None,
+        }
+    }
+
+    pub fn file_track_source(id: i32, title: &str, path: &std::path::Path) -> TrackSource {
+        TrackSource {
+            id,
+            library_entry_id: Some(id),
+            title: title.to_string(),
+            url: Some(format!("file://{}", path.display())),
+            file: None,
+            spotify_id: None,
+            spotify_type: None,
+            local_path: None,
+        }
+    }
+
+    /// Made up from other entries, like "Zuletzt gehört", there is nothing to keep about it
+    pub fn is_virtual(&self) -> bool {
+        self.id < 0
+    }
+}
+
+pub const ROOT_LIBRARY_ENTRY_ID: i32 = 0;
+/// Virtual folder on the home screen with what was played last, it only exists once something was
+pub const RECENTLY_PLAYED_LIBRARY_ENTRY_ID: i32 = -2;
+pub(super) const RECENTLY_PLAYED_LIMIT: usize = 12;
+
+pub(super) fn load_image(name: String) -> Option<Vec<u8>> {
+    let path = format!("{}/images/{}", std::env::current_dir().ok()?.display(), name);
+    std::fs::read(path).ok()
+}
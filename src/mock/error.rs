@@ -0,0 +1,89 @@
+use std::fmt::{Debug, Display};
+
+/// Coarse classification of what went wrong, independent of which component failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    NotFound,
+    Decode,
+    Network,
+    Busy,
+    /// The request itself makes no sense, asking again will not help
+    Invalid,
+}
+
+#[derive(Clone, Debug)]
+pub enum LibraryError {
+    NotFound(i32),
+    Network(String),
+    Decode { id: i32, reason: String },
+    /// An edit that would break the library, like moving a folder into itself
+    Invalid(String),
+}
+
+impl LibraryError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            LibraryError::NotFound(_) => ErrorKind::NotFound,
+            LibraryError::Network(_) => ErrorKind::Network,
+            LibraryError::Decode { .. } => ErrorKind::Decode,
+            LibraryError::Invalid(_) => ErrorKind::Invalid,
+        }
+    }
+}
+
+impl Display for LibraryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LibraryError::NotFound(id) => write!(f, "library entry {} not found", id),
+            LibraryError::Network(reason) => write!(f, "library not reachable: {}", reason),
+            LibraryError::Decode { id, reason } => write!(f, "library entry {} is malformed: {}", id, reason),
+            LibraryError::Invalid(reason) => write!(f, "invalid library edit: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for LibraryError {}
+
+/// The stand-in keeps its files next to the store, failing to write them is what an unreachable
+/// backend would be
+impl From<std::io::Error> for LibraryError {
+    fn from(error: std::io::Error) -> Self {
+        LibraryError::Network(error.to_string())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum PlayerError {
+    NoTrackLoaded,
+    /// The entry has no track source the player could open
+    NoSource(i32),
+    Decode { id: i32, reason: String },
+    Network(String),
+    /// Another command is still in flight
+    Busy,
+}
+
+impl PlayerError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            PlayerError::NoTrackLoaded | PlayerError::NoSource(_) => ErrorKind::NotFound,
+            PlayerError::Decode { .. } => ErrorKind::Decode,
+            PlayerError::Network(_) => ErrorKind::Network,
+            PlayerError::Busy => ErrorKind::Busy,
+        }
+    }
+}
+
+impl Display for PlayerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlayerError::NoTrackLoaded => write!(f, "no track loaded"),
+            PlayerError::NoSource(id) => write!(f, "library entry {} has no track source", id),
+            PlayerError::Decode { id, reason } => write!(f, "could not decode library entry {}: {}", id, reason),
+            PlayerError::Network(reason) => write!(f, "stream not reachable: {}", reason),
+            PlayerError::Busy => write!(f, "player is busy"),
+        }
+    }
+}
+
+impl std::error::Error for PlayerError {}
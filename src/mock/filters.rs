@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+use super::{profiles::{read_profile, write_profile}, LibraryEntry, LibraryError};
+
+/// What a profile gets to see of the library. A hidden folder hides everything inside it.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContentFilter {
+    /// Entries rated for older listeners are hidden, `None` shows every rating
+    pub max_age_rating: Option<u8>,
+    /// Also hides entries without a rating, as long as `max_age_rating` is set
+    pub hide_unrated: bool,
+    /// Genres and tags that hide an entry, case does not matter
+    pub blocked_tags: Vec<String>,
+}
+
+impl ContentFilter {
+    pub(super) fn allows(&self, entry: &LibraryEntry) -> bool {
+        let allowed_rating = match (self.max_age_rating, entry.age_rating) {
+            (None, _) => true,
+            (Some(max), Some(rating)) => rating <= max,
+            (Some(_), None) => !self.hide_unrated,
+        };
+        let blocked = entry.genre.iter().chain(entry.tags.iter())
+            .any(|tag| self.blocked_tags.iter().any(|blocked| blocked.to_lowercase() == tag.to_lowercase()));
+        allowed_rating && !blocked
+    }
+}
+
+/// Any profile's content filter, `None` is the one used without profiles
+pub fn content_filter(profile_id: Option<&str>) -> ContentFilter {
+    read_profile(profile_id, |store| store.filter.clone())
+}
+
+pub fn set_content_filter(profile_id: Option<&str>, filter: ContentFilter) -> Result<(), LibraryError> {
+    if let Some(rating) = filter.max_age_rating.filter(|rating| *rating > 18) {
+        return Err(LibraryError::Invalid(format!("age rating {} is above 18", rating)));
+    }
+    write_profile(profile_id, |store| store.filter = filter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(age_rating: Option<u8>, genre: Option<&str>, tags: &[&str]) -> LibraryEntry {
+        let mut entry = LibraryEntry::new_spotify(1, "Folge 1", None, 0);
+        entry.age_rating = age_rating;
+        entry.genre = genre.map(str::to_string);
+        entry.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        entry
+    }
+
+    #[test]
+    fn allows_everything_by_default() {
+        let filter = ContentFilter::default();
+
+        assert!(filter.allows(&entry(Some(18), Some("Krimi"), &["Gruselig"])));
+        assert!(filter.allows(&entry(None, None, &[])));
+    }
+
+    #[test]
+    fn hides_entries_rated_for_older_listeners() {
+        let filter = ContentFilter { max_age_rating: Some(6), ..Default::default() };
+
+        assert!(filter.allows(&entry(Some(0), None, &[])));
+        assert!(filter.allows(&entry(Some(6), None, &[])));
+        assert!(!filter.allows(&entry(Some(12), None, &[])));
+        assert!(filter.allows(&entry(None, None, &[])));
+    }
+
+    #[test]
+    fn hides_unrated_entries_only_along_with_a_rating() {
+        let hiding = ContentFilter { max_age_rating: Some(6), hide_unrated: true, ..Default::default() };
+        assert!(!hiding.allows(&entry(None, None, &[])));
+        assert!(hiding.allows(&entry(Some(6), None, &[])));
+
+        let without_rating = ContentFilter { hide_unrated: true, ..Default::default() };
+        assert!(without_rating.allows(&entry(None, None, &[])));
+    }
+
+    #[test]
+    fn hides_blocked_genres_and_tags_whatever_their_case() {
+        let filter = ContentFilter { blocked_tags: vec!["krimi".to_string(), "Gruselig".to_string()], ..Default::default() };
+
+        assert!(!filter.allows(&entry(None, Some("Krimi"), &[])));
+        assert!(!filter.allows(&entry(None, None, &["Pferde", "GRUSELIG"])));
+        assert!(filter.allows(&entry(None, Some("Hörspiel"), &["Pferde"])));
+        // Whole tags only, not parts of them
+        assert!(filter.allows(&entry(None, Some("Krimikomödie"), &[])));
+    }
+}
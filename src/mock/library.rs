@@ -0,0 +1,549 @@
+use std::{cmp::Ordering, collections::HashSet, sync::{Mutex, OnceLock}};
+
+use crate::{library_store::{LibraryStore, PodcastStore, ProfileStore}, search_index::SearchIndex};
+
+use super::{
+    entry::{Breadcrumb, RECENTLY_PLAYED_LIBRARY_ENTRY_ID, RECENTLY_PLAYED_LIMIT},
+    LibraryEntry, LibraryError, Profile, SortOrder, Variant, ViewMode, ROOT_LIBRARY_ENTRY_ID,
+};
+
+/// The whole library for the parents, whatever the active profile may see, with the children
+/// of every folder nested inside it
+pub fn library_tree() -> Result<LibraryEntry, LibraryError> {
+    fn nest(entries: &[LibraryEntry], mut entry: LibraryEntry) -> LibraryEntry {
+        if entry.variant.opens() {
+            entry.children = Some(entries.iter()
+                .filter(|child| child.parent_id == Some(entry.id))
+                .map(|child| nest(entries, child.clone()))
+                .collect());
+        }
+        entry
+    }
+    let library = library().lock().unwrap();
+    Ok(nest(&library.entries, library.entry(ROOT_LIBRARY_ENTRY_ID)?.clone()))
+}
+
+/// Any entry's cover for the parents, whatever the active profile may see
+pub fn any_cover(id: i32) -> Result<Option<Vec<u8>>, LibraryError> {
+    Ok(library().lock().unwrap().entry(id)?.image.clone())
+}
+
+/// The cover of an entry the active profile may see
+pub fn cover(id: i32) -> Result<Option<Vec<u8>>, LibraryError> {
+    library_entries().into_iter()
+        .find(|entry| entry.id == id)
+        .map(|entry| entry.image)
+        .ok_or(LibraryError::NotFound(id))
+}
+
+/// Every entry the active profile may see, without children or ancestors resolved
+pub fn scan_library() -> Vec<LibraryEntry> {
+    library_entries()
+}
+
+/// What the active profile sees. Covers are read from disk once, later lookups work on a copy.
+fn library_entries() -> Vec<LibraryEntry> {
+    library().lock().unwrap().visible_entries()
+}
+
+pub(super) struct Library {
+    pub(super) entries: Vec<LibraryEntry>,
+    pub(super) store: LibraryStore,
+    /// `None` when no profiles are set up
+    pub(super) profile: Option<Profile>,
+    /// User data of the active profile, already applied to `entries`
+    pub(super) user: ProfileStore,
+    /// Feeds and their episodes, which are in `entries` as well
+    pub(super) podcasts: PodcastStore,
+    /// Built on the first search
+    pub(super) index: Option<SearchIndex>,
+}
+
+impl Library {
+    /// Changes the user data of entries and writes the result to the active profile's store
+    fn update(&mut self, ids: &[i32], update: impl Fn(&mut LibraryEntry)) -> Result<(), LibraryError> {
+        self.change(ids, update, |_, user, entry| user.update(entry))
+    }
+
+    /// Changes the structure of entries, like their name or place, and writes it to the store
+    pub(super) fn edit(&mut self, ids: &[i32], edit: impl Fn(&mut LibraryEntry)) -> Result<(), LibraryError> {
+        self.change(ids, edit, |store, _, entry| store.edit(entry))
+    }
+
+    fn change(
+        &mut self,
+        ids: &[i32],
+        change: impl Fn(&mut LibraryEntry),
+        record: fn(&mut LibraryStore, &mut ProfileStore, &LibraryEntry),
+    ) -> Result<(), LibraryError> {
+        for id in ids {
+            let entry = self.entries.iter_mut().find(|e| e.id == *id).ok_or(LibraryError::NotFound(*id))?;
+            change(entry);
+            record(&mut self.store, &mut self.user, entry);
+        }
+        self.save();
+        Ok(())
+    }
+
+    pub(super) fn save(&mut self) {
+        self.index = None;
+        if let Err(error) = self.store.save() {
+            eprintln!("Could not save library store: {}", error);
+        }
+        self.save_user();
+    }
+
+    pub(super) fn save_podcasts(&mut self) {
+        self.index = None;
+        if let Err(error) = self.podcasts.save() {
+            eprintln!("Could not save podcast store: {}", error);
+        }
+    }
+
+    /// Only the active profile's store, the library itself did not change
+    pub(super) fn save_user(&self) {
+        if let Err(error) = self.user.save(self.profile_id()) {
+            eprintln!("Could not save profile store: {}", error);
+        }
+    }
+
+    pub(super) fn profile_id(&self) -> Option<&str> {
+        self.profile.as_ref().map(|profile| profile.id.as_str())
+    }
+
+    /// Puts the active profile's favourites, play dates and pins on the entries
+    pub(super) fn apply_user_data(&mut self) {
+        for entry in self.entries.iter_mut() {
+            self.user.entries.get(&entry.id).cloned().unwrap_or_default().apply(entry);
+            entry.pinned = self.user.pinned.contains(&entry.id);
+        }
+    }
+
+    /// The folder the active profile sees as its home, a missing one falls back to the root
+    fn home_id(&self) -> i32 {
+        match self.profile.as_ref().and_then(|profile| profile.root) {
+            Some(root) if self.folder(root).is_ok() => root,
+            _ => ROOT_LIBRARY_ENTRY_ID,
+        }
+    }
+
+    /// The root stands for the active profile's home when something is put there
+    fn real_id(&self, id: i32) -> i32 {
+        if id == ROOT_LIBRARY_ENTRY_ID {
+            self.home_id()
+        } else {
+            id
+        }
+    }
+
+    /// Entries inside the active profile's home that its filter lets through. The home's children
+    /// show up as children of the root, sorted and shown the way the home is.
+    fn visible_entries(&self) -> Vec<LibraryEntry> {
+        let home_id = self.home_id();
+        let home = self.entry(home_id).ok().map(|home| (home.sort_order, home.view_mode));
+        let hidden = self.hidden_ids();
+        let inside = (home_id != ROOT_LIBRARY_ENTRY_ID).then(|| self.subtree(home_id));
+        self.entries.iter()
+            .filter(|entry| entry.id == ROOT_LIBRARY_ENTRY_ID || !hidden.contains(&entry.id))
+            .filter(|entry| match &inside {
+                Some(inside) => entry.id == ROOT_LIBRARY_ENTRY_ID || (entry.id != home_id && inside.contains(&entry.id)),
+                None => true,
+            })
+            .map(|entry| {
+                let mut entry = entry.clone();
+                if entry.parent_id == Some(home_id) {
+                    entry.parent_id = Some(ROOT_LIBRARY_ENTRY_ID);
+                }
+                if let (ROOT_LIBRARY_ENTRY_ID, Some((sort_order, view_mode))) = (entry.id, home) {
+                    entry.sort_order = sort_order;
+                    entry.view_mode = view_mode;
+                }
+                entry
+            })
+            .collect()
+    }
+
+    /// Entries the active profile's filter does not allow, with everything inside them
+    fn hidden_ids(&self) -> HashSet<i32> {
+        let mut hidden = HashSet::new();
+        for entry in self.entries.iter() {
+            if entry.id != ROOT_LIBRARY_ENTRY_ID && !hidden.contains(&entry.id) && !self.user.filter.allows(entry) {
+                hidden.extend(self.subtree(entry.id));
+            }
+        }
+        hidden
+    }
+
+    pub(super) fn entry(&self, id: i32) -> Result<&LibraryEntry, LibraryError> {
+        self.entries.iter().find(|e| e.id == id).ok_or(LibraryError::NotFound(id))
+    }
+
+    /// Only folders take children
+    pub(super) fn folder(&self, id: i32) -> Result<&LibraryEntry, LibraryError> {
+        let entry = self.entry(id)?;
+        if entry.variant != Variant::Folder {
+            return Err(LibraryError::Invalid(format!("{} is not a folder", entry.name)));
+        }
+        Ok(entry)
+    }
+
+    /// The root stays where it is and keeps its name
+    pub(super) fn editable(&self, id: i32) -> Result<&LibraryEntry, LibraryError> {
+        if id == ROOT_LIBRARY_ENTRY_ID {
+            return Err(LibraryError::Invalid("the root cannot be changed".to_string()));
+        }
+        self.entry(id)
+    }
+
+    /// Ids of deleted entries are not handed out again, the store still knows about them
+    pub(super) fn next_id(&self) -> i32 {
+        let highest = self.entries.iter().map(|e| e.id)
+            .chain(self.store.deleted.iter().copied())
+            .chain(self.store.edits.keys().copied())
+            .max()
+            .unwrap_or(ROOT_LIBRARY_ENTRY_ID);
+        highest + 1
+    }
+
+    /// New children go to the end of their folder
+    pub(super) fn next_sort_key(&self, parent_id: i32) -> i32 {
+        self.entries.iter()
+            .filter(|e| e.parent_id == Some(parent_id))
+            .map(|e| e.sort_key + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The entry and everything below it, parents before their children
+    pub(super) fn subtree(&self, id: i32) -> Vec<i32> {
+        let mut ids = vec![id];
+        let mut next = 0;
+        while let Some(parent_id) = ids.get(next).copied() {
+            ids.extend(self.entries.iter().filter(|e| e.parent_id == Some(parent_id)).map(|e| e.id));
+            next += 1;
+        }
+        ids
+    }
+
+    pub(super) fn add(&mut self, mut entry: LibraryEntry) {
+        entry.added_at = Some(chrono::Utc::now());
+        self.store.edit(&entry);
+        self.entries.push(entry);
+    }
+}
+
+pub(super) fn entry_name(name: &str) -> Result<String, LibraryError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(LibraryError::Invalid("the name is empty".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+pub(super) fn library() -> &'static Mutex<Library> {
+    static LIBRARY: OnceLock<Mutex<Library>> = OnceLock::new();
+    LIBRARY.get_or_init(|| {
+        let store = LibraryStore::load();
+        let user = ProfileStore::load(None);
+        let mut podcasts = PodcastStore::load();
+        let mut entries = mock_entries();
+        // Before the edits, episodes can be renamed or moved like anything else
+        for (podcast_id, subscription) in podcasts.subscriptions.iter() {
+            for (sort_key, episode) in subscription.episodes.iter().enumerate() {
+                entries.push(LibraryEntry::new_episode(*podcast_id, episode, sort_key as i32));
+            }
+        }
+        entries.retain(|entry| !store.deleted.contains(&entry.id));
+        for (id, edit) in store.edits.iter() {
+            match entries.iter_mut().find(|e| e.id == *id) {
+                Some(entry) => edit.apply(entry),
+                // Created by hand, the scan knows nothing about it
+                None => {
+                    let mut entry = LibraryEntry::new_folder(*id, &edit.name, edit.parent_id, "".to_string(), edit.sort_key);
+                    if edit.variant != Variant::Folder {
+                        entry.children = None;
+                    }
+                    edit.apply(&mut entry);
+                    entries.push(entry);
+                }
+            }
+        }
+        store.prune_media();
+        podcasts.prune(|id| entries.iter().any(|entry| entry.id == id));
+        let mut library = Library { entries, store, profile: None, user, podcasts, index: None };
+        library.apply_user_data();
+        Mutex::new(library)
+    })
+}
+
+fn mock_entries() -> Vec<LibraryEntry> {
+    vec![        
+        LibraryEntry::new_folder(0, "Root", Some(-1), "".to_string(), 0),
+        LibraryEntry::new_folder(1, "Hoerspiele", Some(0), "hoerspiele.jpeg".to_string(), 0),
+        LibraryEntry::new_folder(2, "Radio", Some(0), "radio.jpeg".to_string(), 1),
+        LibraryEntry::new_folder(3, "Musik", Some(0), "musik.jpeg".to_string(), 2),
+        LibraryEntry::new_folder(13, "Hoerspiele", Some(0), "hoerspiele.jpeg".to_string(), 0),
+        LibraryEntry::new_folder(14, "Radio", Some(0), "radio.jpeg".to_string(), 1),
+        LibraryEntry::new_folder(15, "Musik", Some(0), "musik.jpeg".to_string(), 2),
+        LibraryEntry::new_folder(16, "Hoerspiele", Some(0), "hoerspiele.jpeg".to_string(), 0),
+        LibraryEntry::new_folder(17, "Radio", Some(0), "radio.jpeg".to_string(), 1),
+        LibraryEntry::new_folder(18, "Musik", Some(0), "musik.jpeg".to_string(), 2),
+        LibraryEntry::new_folder(4, "Aquaparty", Some(3), "aquaparty.jpeg".to_string(), 0),
+        LibraryEntry::new_folder(5, "Boomschakalaka", Some(1), "boomschakalaka.jpeg".to_string(), 0),
+        LibraryEntry::new_folder(6, "Kitahits", Some(3), "kitahits.jpeg".to_string(), 1),
+        LibraryEntry::new_spotify(7, "Spotify Track 1", Some(4), 0),
+        LibraryEntry::new_spotify(8, "Spotify Track 2", Some(4), 1),
+        LibraryEntry::new_spotify(9, "Spotify Track 3", Some(4), 2),
+        LibraryEntry::new_spotify(10, "Spotify Track 4", Some(6), 0),
+        LibraryEntry::new_spotify(11, "Spotify Track 5", Some(6), 1),
+        LibraryEntry::new_spotify(12, "Spotify Track 6", Some(6), 2),
+        LibraryEntry::new_spotify(19, "Spotify Track 7", Some(3), 3),
+        LibraryEntry::new_spotify(20, "Spotify Track 8", Some(3), 4),
+        LibraryEntry::new_spotify(21, "Folge 10", Some(5), 0),
+        LibraryEntry::new_spotify(22, "Folge 2", Some(5), 0),
+        LibraryEntry::new_spotify(23, "Folge 1", Some(5), 0),
+    ].into_iter().map(|mut entry| {
+        // Pretend the entries were added one day apart, in id order
+        entry.added_at = chrono::DateTime::from_timestamp(1_700_000_000 + entry.id as i64 * 86_400, 0);
+        entry.genre = match entry.id {
+            1 | 5 | 13 | 16 | 21..=23 => Some("Hörspiel".to_string()),
+            3 | 4 | 6 | 15 | 18 => Some("Musik".to_string()),
+            _ => None,
+        };
+        entry.age_rating = match entry.id {
+            4 | 6 => Some(0),
+            21..=23 => Some(6),
+            _ => None,
+        };
+        entry.tags = match entry.id {
+            4 => vec!["Party".to_string(), "Sommer".to_string()],
+            6 => vec!["Kinderlieder".to_string()],
+            21..=23 => vec!["Abenteuer".to_string()],
+            _ => vec![],
+        };
+        entry
+    }).collect()
+}
+
+/// The entry the device's root stands for, the active profile's home. The edits below take the
+/// ids of the library as it is, like the admin page shows it.
+pub fn real_id(id: i32) -> i32 {
+    library().lock().unwrap().real_id(id)
+}
+
+/// `None` goes back to choosing the view from the children
+pub fn set_view_mode(id: i32, view_mode: Option<ViewMode>) -> Result<(), LibraryError> {
+    library().lock().unwrap().update(&[id], |entry| entry.view_mode = view_mode)
+}
+
+pub fn set_sort_order(id: i32, sort_order: SortOrder) -> Result<(), LibraryError> {
+    library().lock().unwrap().update(&[id], |entry| entry.sort_order = sort_order)
+}
+
+pub fn set_favourite(id: i32, favourite: bool) -> Result<(), LibraryError> {
+    library().lock().unwrap().update(&[id], |entry| entry.favourite = favourite)
+}
+
+/// Pins go to the end of the home screen's pinned entries
+pub fn set_pinned(id: i32, pinned: bool) -> Result<(), LibraryError> {
+    let mut library = library().lock().unwrap();
+    if !library.entries.iter().any(|e| e.id == id) {
+        return Err(LibraryError::NotFound(id));
+    }
+    library.user.pinned.retain(|pinned_id| *pinned_id != id);
+    if pinned {
+        library.user.pinned.push(id);
+    }
+    library.update(&[id], |entry| entry.pinned = pinned)
+}
+
+/// In the order they were pinned
+fn pinned_entries(entries: &[LibraryEntry]) -> Vec<LibraryEntry> {
+    let pinned = library().lock().unwrap().user.pinned.clone();
+    pinned.iter().filter_map(|id| entries.iter().find(|e| e.id == *id).cloned()).collect()
+}
+
+/// Entries matching every word of the query, see `SearchIndex` for how words match
+pub fn search_library(query: &str) -> Result<Vec<LibraryEntry>, LibraryError> {
+    let mut library = library().lock().unwrap();
+    let library = &mut *library;
+    let entries = library.visible_entries();
+    let index = library.index.get_or_insert_with(|| SearchIndex::build(&entries));
+    let results = index.search(query).into_iter()
+        .filter(|id| *id != ROOT_LIBRARY_ENTRY_ID)
+        .filter_map(|id| entries.iter().find(|e| e.id == id))
+        .map(|entry| {
+            let mut entry = entry.clone();
+            entry.parent_name = entries.iter()
+                .find(|e| Some(e.id) == entry.parent_id)
+                .map(|parent| parent.name.clone());
+            entry
+        })
+        .collect();
+    Ok(results)
+}
+
+/// Stamps the entry and every folder above it, so folders sort by what was last played inside
+pub fn mark_played(id: i32, played_at: chrono::DateTime<chrono::Utc>) -> Result<(), LibraryError> {
+    let mut library = library().lock().unwrap();
+    let entry = library.entries.iter().find(|e| e.id == id).ok_or(LibraryError::NotFound(id))?;
+    let mut ids: Vec<i32> = resolve_ancestors(&library.entries, entry).iter().map(|crumb| crumb.id).collect();
+    ids.push(id);
+    library.update(&ids, |entry| entry.played_at = Some(played_at))
+}
+
+/// Played tracks and streams, most recent first; folders are stamped as well but left out here
+fn recently_played_folder(entries: &[LibraryEntry]) -> Option<LibraryEntry> {
+    let mut played: Vec<LibraryEntry> = entries.iter()
+        .filter(|e| e.played_at.is_some() && !e.variant.opens())
+        .cloned()
+        .collect();
+    if played.is_empty() {
+        return None;
+    }
+    sort_children(&mut played, SortOrder::RecentlyPlayed);
+    played.truncate(RECENTLY_PLAYED_LIMIT);
+
+    let mut folder = LibraryEntry::new_folder(RECENTLY_PLAYED_LIBRARY_ENTRY_ID, "Zuletzt gehört", Some(ROOT_LIBRARY_ENTRY_ID), "".to_string(), -1);
+    folder.sort_order = SortOrder::RecentlyPlayed;
+    folder.played_at = played.first().and_then(|e| e.played_at);
+    folder.children = Some(played);
+    Some(folder)
+}
+
+/// Compares names the way people count, "Folge 2" comes before "Folge 10"
+pub(super) fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let take_number = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                        digits.push(digit);
+                    }
+                    digits.trim_start_matches('0').to_string()
+                };
+                let (x, y) = (take_number(&mut a), take_number(&mut b));
+                // Without leading zeros the longer number is the bigger one
+                let ordering = x.len().cmp(&y.len()).then_with(|| x.cmp(&y));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.to_lowercase().cmp(y.to_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+fn sort_children(children: &mut [LibraryEntry], sort_order: SortOrder) {
+    children.sort_by(|a, b| {
+        let ordering = match sort_order {
+            SortOrder::Manual => Ordering::Equal,
+            SortOrder::Name => natural_cmp(&a.name, &b.name),
+            // `None` is less than any date, so reversing puts never played entries last
+            SortOrder::RecentlyPlayed => b.played_at.cmp(&a.played_at),
+            SortOrder::DateAdded => b.added_at.cmp(&a.added_at),
+        };
+        ordering
+            .then_with(|| a.sort_key.cmp(&b.sort_key))
+            .then_with(|| natural_cmp(&a.name, &b.name))
+    });
+}
+
+/// Walks up the parent chain, returns the ancestors root first
+pub(super) fn resolve_ancestors(entries: &[LibraryEntry], entry: &LibraryEntry) -> Vec<Breadcrumb> {
+    let mut ancestors = vec![];
+    let mut parent_id = entry.parent_id;
+    while let Some(parent) = parent_id.and_then(|id| entries.iter().find(|e| e.id == id)) {
+        // Guard against broken data pointing in circles
+        if ancestors.iter().any(|crumb: &Breadcrumb| crumb.id == parent.id) {
+            break;
+        }
+        ancestors.push(Breadcrumb { id: parent.id, name: parent.name.clone() });
+        parent_id = parent.parent_id;
+    }
+    ancestors.reverse();
+    ancestors
+}
+
+pub fn get_library_entry(id: i32) -> Result<LibraryEntry, LibraryError> {
+    let entries = library_entries();
+
+    let entry = if id == RECENTLY_PLAYED_LIBRARY_ENTRY_ID {
+        recently_played_folder(&entries)
+    } else {
+        entries.iter().find(|e| e.id == id).cloned()
+    };
+    if let Some(mut entry) = entry {
+        let mut children: Vec<LibraryEntry> = match entry.children.take() {
+            Some(children) if id == RECENTLY_PLAYED_LIBRARY_ENTRY_ID => children,
+            _ => entries.iter().filter(|e| e.parent_id == Some(entry.id)).cloned().collect(),
+        };
+        if id == ROOT_LIBRARY_ENTRY_ID {
+            children.extend(recently_played_folder(&entries).map(|mut folder| {
+                folder.children = Some(vec![]);
+                folder
+            }));
+        }
+        sort_children(&mut children, entry.sort_order);
+        if id == ROOT_LIBRARY_ENTRY_ID {
+            // Pins come first and are not repeated further down
+            let mut pinned = pinned_entries(&entries);
+            children.retain(|child| !child.pinned);
+            pinned.append(&mut children);
+            children = pinned;
+        }
+        entry.children = Some(children);
+        entry.ancestors = Some(resolve_ancestors(&entries, &entry));
+        if let Some(parent) = entries.iter().find(|e| Some(e.id) == entry.parent_id) {
+            entry.parent_name = Some(parent.name.clone());
+            entry.parent_image = parent.image.clone();
+        }
+        return Ok(entry);
+    }
+
+    Err(LibraryError::NotFound(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(names: &[&str]) -> Vec<String> {
+        let mut names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+        names.sort_by(|a, b| natural_cmp(a, b));
+        names
+    }
+
+    #[test]
+    fn counts_numbers_instead_of_comparing_digits() {
+        assert_eq!(sorted(&["Folge 10", "Folge 2", "Folge 1"]), ["Folge 1", "Folge 2", "Folge 10"]);
+        assert_eq!(sorted(&["2 Teil b", "10 Teil a", "2 Teil a"]), ["2 Teil a", "2 Teil b", "10 Teil a"]);
+        assert_eq!(natural_cmp("Folge 007", "Folge 7"), Ordering::Equal);
+        assert_eq!(natural_cmp("Folge 010", "Folge 9"), Ordering::Greater);
+    }
+
+    #[test]
+    fn ignores_case_and_puts_prefixes_first() {
+        assert_eq!(natural_cmp("bibi", "Bibi"), Ordering::Equal);
+        assert_eq!(sorted(&["benjamin", "Bibi", "Anton"]), ["Anton", "benjamin", "Bibi"]);
+        assert_eq!(sorted(&["Folge 1 Teil 2", "Folge 1"]), ["Folge 1", "Folge 1 Teil 2"]);
+        assert_eq!(natural_cmp("", "a"), Ordering::Less);
+    }
+
+    #[test]
+    fn numbers_too_long_for_any_integer_still_compare() {
+        assert_eq!(natural_cmp("99999999999999999999999", "100000000000000000000000"), Ordering::Less);
+    }
+}
@@ -0,0 +1,29 @@
+//! Stand-in for the backend API: the library with its edits and podcasts, what each profile may
+//! do and see, and a player that only pretends to play
+
+mod edits;
+mod entry;
+mod error;
+mod filters;
+mod library;
+mod player;
+mod podcasts;
+mod profiles;
+
+pub use edits::{
+    backup_entries, backup_subtree, cover_image, cover_images, create_folder, create_track, delete_entry, move_entry,
+    pick_cover, rename_entry, reorder_children, restore_entries, set_cover, set_labels, EntryBackup,
+};
+pub use entry::{LibraryEntry, SortOrder, Variant, ViewMode, ROOT_LIBRARY_ENTRY_ID};
+pub use error::{ErrorKind, LibraryError, PlayerError};
+pub use filters::{content_filter, set_content_filter, ContentFilter};
+pub use library::{
+    any_cover, cover, get_library_entry, library_tree, mark_played, real_id, scan_library, search_library, set_favourite,
+    set_pinned, set_sort_order, set_view_mode,
+};
+pub use player::{Player, PlayerEvent, Progress};
+pub use podcasts::{create_podcast, episode_downloaded, episode_played, episode_too_large, pending_downloads, podcast_feeds, update_podcast};
+pub use profiles::{
+    grant_extra_minutes, may_listen, parental_limits, profile_limits, record_listening, save_session, session,
+    set_parental_limits, switch_profile, ListeningUsage, ParentalLimits, Profile,
+};
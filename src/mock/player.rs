@@ -0,0 +1,341 @@
+use std::{fs::File, io::Read, path::Path, sync::{mpsc::{channel, Sender}, Arc, Mutex}, thread::spawn, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use super::{entry::TrackSource, LibraryEntry, PlayerError};
+
+#[derive(Default)]
+struct PlayerInner {
+    is_playing: bool,
+    current_position: Duration,
+    current_track: Option<LibraryEntry>,
+    volume: f32,
+    /// A command is running, see `Player::begin`
+    busy: bool,
+    subscribers: Vec<Sender<PlayerEvent>>
+}
+
+#[derive(Clone)]
+pub struct Player {
+    inner: Arc<Mutex<PlayerInner>>
+}
+
+/// How often the player reports its position
+const CLOCK_TICK: Duration = Duration::from_secs(1);
+/// Every mock track is this long
+const TRACK_DURATION: Duration = Duration::from_secs(100);
+/// Between 0 and 1, loud enough to hear but not to wake anyone
+const DEFAULT_VOLUME: f32 = 0.5;
+/// Steps a fade out takes, enough for the ear not to notice them
+const FADE_STEPS: u32 = 20;
+/// Enough of the start of a track to tell what it is
+const HEADER_LENGTH: usize = 16;
+/// A stream that takes longer to answer is as good as unreachable
+const STREAM_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Marks the player busy until dropped
+struct Command {
+    inner: Arc<Mutex<PlayerInner>>,
+}
+
+impl Drop for Command {
+    fn drop(&mut self) {
+        self.inner.lock().unwrap().busy = false;
+    }
+}
+
+impl Player {
+    pub fn new() -> Self {
+        let player = Player {
+            inner: Arc::new(Mutex::new(PlayerInner { volume: DEFAULT_VOLUME, ..Default::default() }))
+        };
+        player.spawn_clock();
+        player
+    }
+
+    /// Advances the position of the playing track and reports it, like a real player would
+    fn spawn_clock(&self) {
+        let inner = Arc::downgrade(&self.inner);
+        spawn(move || loop {
+            std::thread::sleep(CLOCK_TICK);
+            let Some(inner) = inner.upgrade() else {
+                break;
+            };
+            let player = Player { inner };
+
+            let mut inner = player.inner.lock().unwrap();
+            if !inner.is_playing {
+                continue;
+            }
+            inner.current_position = (inner.current_position + CLOCK_TICK).min(TRACK_DURATION);
+            let progress = Progress { position: inner.current_position, duration: TRACK_DURATION };
+            let finished = inner.current_position >= TRACK_DURATION;
+            if finished {
+                inner.is_playing = false;
+            }
+            drop(inner);
+
+            player.notify(PlayerEvent::PositionChanged(progress));
+            if finished {
+                player.notify(PlayerEvent::Finished);
+            }
+        });
+    }
+
+    /// Claims the player for one command, like a real one it handles them one at a time
+    fn begin(&self) -> Result<Command, PlayerError> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.busy {
+            return Err(PlayerError::Busy);
+        }
+        inner.busy = true;
+        Ok(Command { inner: self.inner.clone() })
+    }
+
+    /// Reads the start of the track like a decoder would, the mock plays nothing but still turns
+    /// away sources a real player could not open
+    async fn open(&self, entry_id: i32, source: &TrackSource) -> Result<(), PlayerError> {
+        let id = source.library_entry_id.unwrap_or(entry_id);
+        let decode_error = |reason: String| PlayerError::Decode {
+            id,
+            reason: format!("track source {} \"{}\": {}", source.id, source.title, reason),
+        };
+        let header = if let Some(file) = &source.file {
+            file.iter().copied().take(HEADER_LENGTH).collect()
+        } else {
+            let Some(location) = source.location() else {
+                return Err(PlayerError::NoSource(entry_id));
+            };
+            if let Some(path) = location.strip_prefix("file://") {
+                read_header(Path::new(path)).map_err(|error| decode_error(error.to_string()))?
+            } else if location.starts_with("http://") || location.starts_with("https://") {
+                fetch_header(&location).await.map_err(|error| PlayerError::Network(error.to_string()))?
+            } else {
+                // Spotify plays in its own app
+                return Ok(());
+            }
+        };
+        if !is_audio(&header) {
+            return Err(decode_error("not audio".to_string()));
+        }
+        Ok(())
+    }
+
+    async fn random_delay(&self) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as u64;
+        
+        let delay_ms = now % 1000;
+        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+    }
+
+    pub async fn play(&self, library_entry: LibraryEntry) -> Result<(), PlayerError> {
+        let _command = self.begin()?;
+        self.random_delay().await;
+        let Some(source) = &library_entry.track_source else {
+            return Err(PlayerError::NoSource(library_entry.id));
+        };
+        self.open(library_entry.id, source).await?;
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.is_playing = true;
+        inner.current_track = Some(library_entry.clone());
+        inner.current_position = Duration::ZERO;
+        drop(inner);
+
+        self.notify(PlayerEvent::Playing(library_entry));
+
+        Ok(())
+    }
+
+    pub async fn pause(&self) -> Result<(), PlayerError> {
+        let _command = self.begin()?;
+        self.random_delay().await;
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.is_playing = false;
+        drop(inner);
+
+        self.notify(PlayerEvent::Paused);
+
+        Ok(())
+    }
+
+    pub async fn resume(&self) -> Result<(), PlayerError> {
+        let _command = self.begin()?;
+        self.random_delay().await;
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.current_track.is_none() {
+            return Err(PlayerError::NoTrackLoaded);
+        }
+
+        inner.is_playing = true;
+        drop(inner);
+
+        self.notify(PlayerEvent::Resumed);
+
+        Ok(())
+    }
+
+    pub async fn seek(&self, position: Duration) -> Result<(), PlayerError> {
+        let _command = self.begin()?;
+        self.random_delay().await;
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.current_track.is_none() {
+            return Err(PlayerError::NoTrackLoaded);
+        }
+        inner.current_position = position.min(TRACK_DURATION);
+
+        Ok(())
+    }
+
+    pub async fn stop(&self) -> Result<(), PlayerError> {
+        let _command = self.begin()?;
+        self.random_delay().await;
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.current_track = None;
+        inner.is_playing = false;
+        drop(inner);
+
+        self.notify(PlayerEvent::Stopped);
+
+        Ok(())
+    }
+
+    pub async fn set_volume(&self, volume: f32) -> Result<(), PlayerError> {
+        self.random_delay().await;
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.volume = volume.clamp(0.0, 1.0);
+
+        Ok(())
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.inner.lock().unwrap().volume
+    }
+
+    /// Turns the volume down over `duration` and pauses, the volume is back where it was for the
+    /// next time
+    pub async fn fade_out(&self, duration: Duration) -> Result<(), PlayerError> {
+        let _command = self.begin()?;
+        let volume = self.volume();
+        for step in (0..FADE_STEPS).rev() {
+            tokio::time::sleep(duration / FADE_STEPS).await;
+            self.inner.lock().unwrap().volume = volume * step as f32 / FADE_STEPS as f32;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.is_playing = false;
+        inner.volume = volume;
+        drop(inner);
+
+        self.notify(PlayerEvent::Paused);
+
+        Ok(())
+    }
+
+    fn notify(&self, event: PlayerEvent) {
+        let inner = self.inner.lock().unwrap();
+        for subscriber in inner.subscribers.iter() {
+            subscriber.send(event.clone()).unwrap();
+        }
+    }
+
+    pub fn subscribe<C>(&self, callback: C) -> Result<(), PlayerError>
+    where
+        C: Fn(PlayerEvent) + Send + 'static
+    {
+        let (sender, receiver) = channel::<PlayerEvent>();
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.subscribers.push(sender);
+
+        spawn(move || {
+            for event in receiver {
+                callback(event);
+            }
+        });
+
+        Ok(())
+    }
+}
+
+fn read_header(path: &Path) -> std::io::Result<Vec<u8>> {
+    let mut header = Vec::with_capacity(HEADER_LENGTH);
+    File::open(path)?.take(HEADER_LENGTH as u64).read_to_end(&mut header)?;
+    Ok(header)
+}
+
+async fn fetch_header(url: &str) -> Result<Vec<u8>, reqwest::Error> {
+    let response = reqwest::Client::builder()
+        .timeout(STREAM_TIMEOUT)
+        .build()?
+        .get(url)
+        .header(reqwest::header::RANGE, format!("bytes=0-{}", HEADER_LENGTH - 1))
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(response.bytes().await?.iter().copied().take(HEADER_LENGTH).collect())
+}
+
+/// Recognises MP3, Ogg, FLAC, WAV and MP4 audio by their first bytes
+fn is_audio(header: &[u8]) -> bool {
+    [&b"ID3"[..], b"OggS", b"fLaC", b"RIFF"].iter().any(|magic| header.starts_with(magic))
+        || header.get(4..8) == Some(&b"ftyp"[..])
+        // MPEG frame sync, for MP3s without tags
+        || matches!(header, [0xFF, second, ..] if second & 0xE0 == 0xE0)
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    position: Duration,
+    duration: Duration
+}
+
+impl Progress {
+    pub fn position(&self) -> Duration {
+        self.position
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Same track at another position, kept within the track
+    pub fn with_position(&self, position: Duration) -> Progress {
+        Progress { position: position.min(self.duration), duration: self.duration }
+    }
+
+    /// How much of the track has been played, between 0 and 1
+    pub fn fraction(&self) -> f32 {
+        if self.duration.is_zero() {
+            return 0.0;
+        }
+        (self.position.as_secs_f32() / self.duration.as_secs_f32()).clamp(0.0, 1.0)
+    }
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self { position: Duration::ZERO, duration: TRACK_DURATION }
+    }
+}
+
+#[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum PlayerEvent {
+    PositionChanged(Progress),
+    /// The entry that started, the app itself takes it from its state but a backend reports it
+    Playing(#[allow(dead_code)] LibraryEntry),
+    Paused,
+    Resumed,
+    Stopped,
+    /// The track played to its end
+    Finished,
+}
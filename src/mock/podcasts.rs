@@ -0,0 +1,183 @@
+use crate::{library_store::{self, StoredEpisode, Subscription}, podcasts::Feed};
+
+use super::{library::{entry_name, library, Library}, LibraryEntry, LibraryError};
+
+impl Library {
+    /// Takes the feed's episodes in, new ones get an id and an entry. Episodes the feed dropped
+    /// go too, unless they are downloaded and still waiting to be listened to. Returns whether
+    /// anything changed.
+    fn merge_episodes(&mut self, podcast_id: i32, feed: &Feed) -> bool {
+        let Some(known) = self.podcasts.subscriptions.get(&podcast_id).map(|subscription| subscription.episodes.clone()) else {
+            return false;
+        };
+        let mut next_id = self.next_id();
+        let mut changed = false;
+        let mut episodes: Vec<StoredEpisode> = Vec::new();
+        for item in feed.episodes.iter() {
+            // Feeds repeat themselves now and then
+            if episodes.iter().any(|episode| episode.guid == item.guid) {
+                continue;
+            }
+            let episode = match known.iter().find(|episode| episode.guid == item.guid) {
+                Some(episode) => episode.clone(),
+                None => {
+                    next_id += 1;
+                    StoredEpisode { id: next_id - 1, guid: item.guid.clone(), ..Default::default() }
+                }
+            };
+            let updated = StoredEpisode {
+                title: item.title.clone(),
+                url: item.url.clone(),
+                published: item.published,
+                length: item.length,
+                // A new enclosure is a new file
+                known_length: if item.url == episode.url { episode.known_length } else { 0 },
+                ..episode.clone()
+            };
+            changed |= updated.title != episode.title || updated.url != episode.url || updated.published != episode.published;
+            episodes.push(updated);
+        }
+        let gone: Vec<StoredEpisode> = known.iter().filter(|episode| !episodes.iter().any(|kept| kept.id == episode.id)).cloned().collect();
+        for episode in gone {
+            if episode.download.is_some() && !episode.played {
+                episodes.push(episode);
+            } else {
+                changed = true;
+            }
+        }
+        if !changed {
+            return false;
+        }
+        // Newest first, undated ones keep the feed's order behind them
+        episodes.sort_by(|a, b| b.published.is_some().cmp(&a.published.is_some()).then_with(|| b.published.cmp(&a.published)));
+
+        for dropped in known.iter().filter(|episode| !episodes.iter().any(|kept| kept.id == episode.id)) {
+            self.entries.retain(|entry| entry.id != dropped.id);
+            self.store.forget(dropped.id);
+            self.user.forget(dropped.id);
+        }
+        for (sort_key, episode) in episodes.iter().enumerate() {
+            let mut entry = LibraryEntry::new_episode(podcast_id, episode, sort_key as i32);
+            match self.entries.iter_mut().find(|entry| entry.id == episode.id) {
+                Some(existing) => {
+                    existing.name = entry.name;
+                    existing.sort_key = entry.sort_key;
+                    existing.added_at = entry.added_at;
+                    existing.track_source = entry.track_source;
+                }
+                // The parents deleted it, it stays known so it does not come back
+                None if self.store.deleted.contains(&episode.id) => {}
+                None => {
+                    self.user.entries.get(&entry.id).cloned().unwrap_or_default().apply(&mut entry);
+                    self.entries.push(entry);
+                }
+            }
+        }
+        if let Some(subscription) = self.podcasts.subscriptions.get_mut(&podcast_id) {
+            subscription.episodes = episodes;
+        }
+        self.save_podcasts();
+        true
+    }
+
+    /// Podcasts still in the library, a deleted one stays subscribed until the next start in case
+    /// it is brought back by undoing
+    fn podcast_feeds(&self) -> Vec<(i32, String)> {
+        self.podcasts.subscriptions.iter()
+            .filter(|(id, _)| self.entries.iter().any(|entry| entry.id == **id))
+            .map(|(id, subscription)| (*id, subscription.url.clone()))
+            .collect()
+    }
+
+    /// Changes a stored episode and rebuilds its entry's track source and played state
+    fn update_episode(&mut self, id: i32, update: impl FnOnce(&mut StoredEpisode)) -> Result<(), LibraryError> {
+        let (podcast_id, episode) = self.podcasts.subscriptions.iter_mut()
+            .find_map(|(podcast_id, subscription)| Some((*podcast_id, subscription.episodes.iter_mut().find(|episode| episode.id == id)?)))
+            .ok_or(LibraryError::NotFound(id))?;
+        update(episode);
+        let rebuilt = LibraryEntry::new_episode(podcast_id, episode, 0);
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) {
+            entry.unplayed = rebuilt.unplayed;
+            entry.track_source = rebuilt.track_source;
+        }
+        self.save_podcasts();
+        Ok(())
+    }
+}
+
+/// Subscribes to a feed that was just fetched, at the end of its parent. Returns the podcast's id.
+pub fn create_podcast(parent_id: i32, url: &str, feed: &Feed) -> Result<i32, LibraryError> {
+    let mut library = library().lock().unwrap();
+    library.folder(parent_id)?;
+    if library.podcast_feeds().iter().any(|(_, feed_url)| feed_url == url) {
+        return Err(LibraryError::Invalid(format!("{} is already subscribed", url)));
+    }
+    let (id, sort_key) = (library.next_id(), library.next_sort_key(parent_id));
+    library.add(LibraryEntry::new_podcast(id, &entry_name(&feed.title)?, Some(parent_id), sort_key));
+    library.podcasts.subscriptions.insert(id, Subscription { url: url.to_string(), episodes: vec![] });
+    library.merge_episodes(id, feed);
+    library.save();
+    library.save_podcasts();
+    Ok(id)
+}
+
+/// Ids and URLs of the podcasts to refresh, deleted ones are left out
+pub fn podcast_feeds() -> Vec<(i32, String)> {
+    library().lock().unwrap().podcast_feeds()
+}
+
+/// Returns whether the episodes changed
+pub fn update_podcast(id: i32, feed: &Feed) -> Result<bool, LibraryError> {
+    let mut library = library().lock().unwrap();
+    if !library.podcasts.subscriptions.contains_key(&id) {
+        return Err(LibraryError::NotFound(id));
+    }
+    Ok(library.merge_episodes(id, feed))
+}
+
+/// Unplayed episodes without a download, newest first across every podcast. Deleted ones are
+/// left out, deleting a podcast deletes its episodes as well.
+pub fn pending_downloads() -> Vec<StoredEpisode> {
+    let library = library().lock().unwrap();
+    let mut pending: Vec<StoredEpisode> = library.podcasts.subscriptions.values()
+        .flat_map(|subscription| subscription.episodes.iter())
+        .filter(|episode| !episode.played && episode.download.is_none())
+        .filter(|episode| library.entries.iter().any(|entry| entry.id == episode.id))
+        .cloned()
+        .collect();
+    pending.sort_by_key(|episode| std::cmp::Reverse(episode.published));
+    pending
+}
+
+/// Fails when the episode was played or dropped meanwhile, the download is not needed then
+pub fn episode_downloaded(id: i32, name: &str) -> Result<(), LibraryError> {
+    let mut library = library().lock().unwrap();
+    let mut played = false;
+    library.update_episode(id, |episode| {
+        played = episode.played;
+        if !played {
+            episode.download = Some(name.to_string());
+        }
+    })?;
+    if played {
+        return Err(LibraryError::Invalid(format!("episode {} was played already", id)));
+    }
+    Ok(())
+}
+
+/// The download stopped after `size` bytes, it is not tried again before that much fits
+pub fn episode_too_large(id: i32, size: u64) -> Result<(), LibraryError> {
+    let mut library = library().lock().unwrap();
+    library.update_episode(id, |episode| episode.known_length = episode.known_length.max(size))
+}
+
+/// Marks the episode as listened to the end and deletes its download, it streams from now on
+pub fn episode_played(id: i32) -> Result<(), LibraryError> {
+    let mut library = library().lock().unwrap();
+    library.update_episode(id, |episode| {
+        episode.played = true;
+        if let Some(name) = episode.download.take() {
+            library_store::remove_download(&name);
+        }
+    })
+}
@@ -0,0 +1,207 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::library_store::{ProfileStore, Session};
+
+use super::{library::library, LibraryError};
+
+/// Limits the parents set for listening
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParentalLimits {
+    /// Listening time per day, `None` for no limit
+    pub daily_minutes: Option<u32>,
+    /// Listening is only allowed between these times of day, a window past midnight wraps around
+    pub allowed_from: Option<chrono::NaiveTime>,
+    pub allowed_until: Option<chrono::NaiveTime>,
+    /// The child lock switches itself on at `lock_from` and off again at `lock_until`
+    pub lock_from: Option<chrono::NaiveTime>,
+    pub lock_until: Option<chrono::NaiveTime>,
+    /// Between 0 and 1, the volume cannot be turned up any further
+    pub max_volume: Option<f32>,
+}
+
+impl ParentalLimits {
+    /// The loudest the volume may be
+    pub fn loudest(&self) -> f32 {
+        self.max_volume.unwrap_or(1.0)
+    }
+
+    /// Whether the scheduled child lock covers this time of day
+    pub fn is_lock_scheduled_at(&self, time: chrono::NaiveTime) -> bool {
+        match (self.lock_from, self.lock_until) {
+            (Some(from), Some(until)) => is_within(from, until, time),
+            _ => false,
+        }
+    }
+
+    /// Whether the daily budget and the allowed hours leave room for listening, extra time aside
+    fn is_within_limits(&self, listened: Duration, time: chrono::NaiveTime) -> bool {
+        let within_budget = self.daily_minutes.is_none_or(|minutes| listened < Duration::from_secs(u64::from(minutes) * 60));
+        let within_hours = match (self.allowed_from, self.allowed_until) {
+            (Some(from), Some(until)) => is_within(from, until, time),
+            _ => true,
+        };
+        within_budget && within_hours
+    }
+}
+
+/// How much was listened on one day, the budget is checked against this
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListeningUsage {
+    pub date: Option<chrono::NaiveDate>,
+    /// Playback time only, pauses do not count
+    pub listened_millis: u64,
+    /// Granted by the parents, used up while listening beyond the limits
+    pub extra_millis: u64,
+}
+
+impl ListeningUsage {
+    /// Today's usage, a new day starts from zero
+    fn on(&mut self, date: chrono::NaiveDate) -> &mut Self {
+        if self.date != Some(date) {
+            *self = ListeningUsage { date: Some(date), ..Default::default() };
+        }
+        self
+    }
+
+    fn listened(&self) -> Duration {
+        Duration::from_millis(self.listened_millis)
+    }
+
+    fn may_listen(&self, limits: &ParentalLimits, time: chrono::NaiveTime) -> bool {
+        limits.is_within_limits(self.listened(), time) || self.extra_millis > 0
+    }
+}
+
+/// One of the kids sharing the device, with their own favourites, resume points and limits
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    /// Names the profile's data file, so it must stay the same once chosen
+    pub id: String,
+    pub name: String,
+    /// Folder the profile sees as its home, the whole library when missing
+    #[serde(default)]
+    pub root: Option<i32>,
+}
+
+/// `from` included and `until` excluded, a window past midnight wraps around
+fn is_within(from: chrono::NaiveTime, until: chrono::NaiveTime, time: chrono::NaiveTime) -> bool {
+    if from <= until {
+        from <= time && time < until
+    } else {
+        time >= from || time < until
+    }
+}
+
+/// Counts playback time against the day's budget and returns whether listening may go on
+pub fn record_listening(played: Duration, now: chrono::NaiveDateTime) -> bool {
+    let mut library = library().lock().unwrap();
+    let store = &mut library.user;
+    let usage = store.usage.on(now.date());
+    let played_millis = played.as_millis() as u64;
+    let minute = usage.listened_millis / 60_000;
+    if !store.limits.is_within_limits(usage.listened(), now.time()) {
+        usage.extra_millis = usage.extra_millis.saturating_sub(played_millis);
+    }
+    usage.listened_millis += played_millis;
+    let may_listen = usage.may_listen(&store.limits, now.time());
+
+    // Once a minute is plenty, a power cut costs the kids at most that
+    if usage.listened_millis / 60_000 != minute || !may_listen {
+        library.save_user();
+    }
+    may_listen
+}
+
+/// Whether the limits, or extra time the parents granted, allow listening right now
+pub fn may_listen(now: chrono::NaiveDateTime) -> bool {
+    let mut library = library().lock().unwrap();
+    let store = &mut library.user;
+    store.usage.on(now.date()).may_listen(&store.limits, now.time())
+}
+
+/// Extra time for today, on top of what is left of any earlier grant
+pub fn grant_extra_minutes(minutes: u32, now: chrono::NaiveDateTime) -> Result<(), LibraryError> {
+    if minutes == 0 || minutes > 24 * 60 {
+        return Err(LibraryError::Invalid(format!("{} extra minutes", minutes)));
+    }
+    let mut library = library().lock().unwrap();
+    library.user.usage.on(now.date()).extra_millis += u64::from(minutes) * 60_000;
+    library.save_user();
+    Ok(())
+}
+
+/// The active profile's limits
+pub fn parental_limits() -> ParentalLimits {
+    library().lock().unwrap().user.limits.clone()
+}
+
+/// Any profile's limits, `None` is the one used without profiles
+pub fn profile_limits(profile_id: Option<&str>) -> ParentalLimits {
+    read_profile(profile_id, |store| store.limits.clone())
+}
+
+pub fn set_parental_limits(profile_id: Option<&str>, limits: ParentalLimits) -> Result<(), LibraryError> {
+    if limits.daily_minutes.is_some_and(|minutes| minutes > 24 * 60) {
+        return Err(LibraryError::Invalid("a day has no more than 1440 minutes".to_string()));
+    }
+    if limits.allowed_from.is_some() != limits.allowed_until.is_some() || limits.lock_from.is_some() != limits.lock_until.is_some() {
+        return Err(LibraryError::Invalid("a time window needs a start and an end".to_string()));
+    }
+    if let Some(volume) = limits.max_volume.filter(|volume| !(0.0..=1.0).contains(volume)) {
+        return Err(LibraryError::Invalid(format!("volume {} is not between 0 and 1", volume)));
+    }
+    write_profile(profile_id, |store| store.limits = limits)
+}
+
+/// The active profile's store is already loaded, any other one is read from disk
+pub(super) fn read_profile<T>(profile_id: Option<&str>, read: impl FnOnce(&ProfileStore) -> T) -> T {
+    let library = library().lock().unwrap();
+    if library.profile_id() == profile_id {
+        return read(&library.user);
+    }
+    read(&ProfileStore::load(profile_id))
+}
+
+pub(super) fn write_profile(profile_id: Option<&str>, write: impl FnOnce(&mut ProfileStore)) -> Result<(), LibraryError> {
+    let mut library = library().lock().unwrap();
+    if library.profile_id() == profile_id {
+        write(&mut library.user);
+        // The filter may have changed what search can find
+        library.index = None;
+        library.save_user();
+        return Ok(());
+    }
+    let mut store = ProfileStore::load(profile_id);
+    write(&mut store);
+    store.save(profile_id).map_err(LibraryError::from)
+}
+
+/// Saves what the active profile did and puts the other profile's favourites, pins and limits in
+/// place. Returns where the other profile left off.
+pub fn switch_profile(profile: Option<Profile>) -> Session {
+    let mut library = library().lock().unwrap();
+    library.save_user();
+    library.user = ProfileStore::load(profile.as_ref().map(|profile| profile.id.as_str()));
+    library.profile = profile;
+    library.apply_user_data();
+    library.index = None;
+    library.user.session.clone()
+}
+
+/// Where the active profile left off
+pub fn session() -> Session {
+    library().lock().unwrap().user.session.clone()
+}
+
+/// Written only when something changed, this runs every few seconds
+pub fn save_session(session: Session) {
+    let mut library = library().lock().unwrap();
+    if library.user.session != session {
+        library.user.session = session;
+        library.save_user();
+    }
+}
@@ -0,0 +1,89 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::{atomic::Ordering, Arc},
+};
+
+use chrono::Local;
+
+use crate::{
+    mock,
+    model::state::{Field, InnerState, State},
+};
+
+/// How many corners have to be tapped to unlock without the PIN
+const GESTURE_LENGTH: usize = 6;
+
+/// How the lock screen was opened again
+#[derive(Debug, Clone)]
+pub enum Unlock {
+    Pin(String),
+    /// The corners tapped in order, 0 is top left and the others follow clockwise
+    Gesture(Vec<u8>),
+}
+
+/// A new sequence at every start, only the parents get to see it. No corner comes twice in a row,
+/// that is what a kid hammering one corner would do.
+pub(in crate::model) fn random_gesture() -> Arc<[u8]> {
+    let mut random = RandomState::new().build_hasher().finish();
+    let mut corners = vec![(random % 4) as u8];
+    random /= 4;
+    while corners.len() < GESTURE_LENGTH {
+        let previous = corners[corners.len() - 1];
+        corners.push((previous + 1 + (random % 3) as u8) % 4);
+        random /= 3;
+    }
+    corners.into()
+}
+
+impl State {
+    /// Leaves edit mode too, the parents' features are not for the kids either
+    pub(in crate::model) fn lock(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        self.set_locked(&mut inner, true);
+    }
+
+    pub(in crate::model) fn unlock(&self, unlock: Unlock) {
+        let mut inner = self.inner.lock().unwrap();
+        match unlock {
+            Unlock::Pin(pin) => {
+                if !self.check_pin(&mut inner, &pin) {
+                    return;
+                }
+            }
+            // No toast, that would tell the kids how close they got
+            Unlock::Gesture(corners) => {
+                if *corners != *self.unlock_gesture {
+                    return;
+                }
+            }
+        }
+        self.set_locked(&mut inner, false);
+    }
+
+    /// Locks when the scheduled lock time starts and unlocks when it ends. Only the edges count, so
+    /// the parents can unlock in between without the next tick locking again.
+    pub(in crate::model) fn tick(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        self.check_lock_schedule(&mut inner);
+        self.check_listening_time(&mut inner);
+        self.check_max_volume(&mut inner);
+        self.save_session(&inner);
+    }
+
+    pub(in crate::model) fn check_lock_schedule(&self, inner: &mut InnerState) {
+        let scheduled = mock::parental_limits().is_lock_scheduled_at(Local::now().time());
+        if self.lock_scheduled.swap(scheduled, Ordering::Relaxed) != scheduled {
+            self.set_locked(inner, scheduled);
+        }
+    }
+
+    fn set_locked(&self, inner: &mut InnerState, locked: bool) {
+        if locked && inner.edit_mode {
+            inner.set(Field::edit_mode(false));
+        }
+        if inner.locked != locked {
+            inner.set(Field::locked(locked));
+        }
+    }
+}
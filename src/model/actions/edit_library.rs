@@ -0,0 +1,123 @@
+use crate::{
+    mock::{self, EntryBackup, LibraryError, SortOrder},
+    model::{state::{Field, InnerState, State}, undo::UndoStep},
+};
+
+impl State {
+    pub(in crate::model) fn enter_edit_mode(&self, pin: String) {
+        let mut inner = self.inner.lock().unwrap();
+        if self.check_pin(&mut inner, &pin) && !inner.edit_mode {
+            inner.set(Field::edit_mode(true));
+        }
+    }
+
+    /// The undo steps are kept, a mistake may only show after leaving
+    pub(in crate::model) fn leave_edit_mode(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.edit_mode {
+            inner.set(Field::edit_mode(false));
+        }
+    }
+
+    pub(in crate::model) fn rename_library_entry(&self, id: i32, name: String) {
+        let mut inner = self.inner.lock().unwrap();
+        self.edit_library(&mut inner, "Umbenennen", mock::backup_entries(&[id]), || mock::rename_entry(id, &name));
+    }
+
+    pub(in crate::model) fn move_library_entry(&self, id: i32, parent_id: i32) {
+        let mut inner = self.inner.lock().unwrap();
+        self.edit_library(&mut inner, "Verschieben", mock::backup_entries(&[id]), || mock::move_entry(id, mock::real_id(parent_id)));
+    }
+
+    /// Moves the entry to where `target_id` is in the shown folder, the folder is arranged by hand
+    /// from then on
+    pub(in crate::model) fn reorder_library_entry(&self, id: i32, target_id: i32) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(folder) = inner.active_library_entry.clone() else {
+            return;
+        };
+        // Only the folder's own children, the home screen also shows pins from elsewhere
+        let mut order: Vec<i32> = folder.children.iter().flatten()
+            .filter(|child| child.parent_id == Some(folder.id))
+            .map(|child| child.id)
+            .collect();
+        let (Some(from), Some(to)) = (order.iter().position(|child| *child == id), order.iter().position(|child| *child == target_id)) else {
+            return;
+        };
+        if from == to {
+            return;
+        }
+        order.remove(from);
+        order.insert(to, id);
+
+        let folder_id = mock::real_id(folder.id);
+        let mut ids = order.clone();
+        ids.push(folder_id);
+        self.edit_library(&mut inner, "Sortieren", mock::backup_entries(&ids), || {
+            mock::reorder_children(folder_id, &order)?;
+            if folder.sort_order != SortOrder::Manual {
+                mock::set_sort_order(folder_id, SortOrder::Manual)?;
+            }
+            Ok(())
+        });
+    }
+
+    pub(in crate::model) fn delete_library_entry(&self, id: i32) {
+        let mut inner = self.inner.lock().unwrap();
+        self.edit_library(&mut inner, "Löschen", mock::backup_subtree(id), || mock::delete_entry(id));
+    }
+
+    /// `name` is one of `mock::cover_images`
+    pub(in crate::model) fn pick_cover(&self, id: i32, name: String) {
+        let mut inner = self.inner.lock().unwrap();
+        self.edit_library(&mut inner, "Cover ändern", mock::backup_entries(&[id]), || mock::pick_cover(id, &name));
+    }
+
+    pub(in crate::model) fn undo_edit(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.edit_mode {
+            return;
+        }
+        let mut undo_stack = inner.undo_stack.clone();
+        let Some(step) = undo_stack.pop() else {
+            return;
+        };
+        match mock::restore_entries(&step.backup) {
+            Ok(()) => {
+                inner.set(Field::undo_stack(undo_stack));
+                self.refresh_active_library_entry(&mut inner);
+            }
+            Err(error) => self.report_error(&mut inner, error, None),
+        }
+    }
+
+    /// Runs an edit with what it touches backed up. Only while editing is unlocked, and an edit
+    /// failing halfway is rolled back right away.
+    fn edit_library(
+        &self,
+        inner: &mut InnerState,
+        label: &str,
+        backup: Result<Vec<EntryBackup>, LibraryError>,
+        edit: impl FnOnce() -> Result<(), LibraryError>,
+    ) {
+        if !inner.edit_mode {
+            return;
+        }
+        let result = backup.and_then(|backup| match edit() {
+            Ok(()) => Ok(backup),
+            Err(error) => {
+                mock::restore_entries(&backup).ok();
+                Err(error)
+            }
+        });
+        match result {
+            Ok(backup) => {
+                let mut undo_stack = inner.undo_stack.clone();
+                undo_stack.push(UndoStep { label: label.to_string(), backup });
+                inner.set(Field::undo_stack(undo_stack));
+                self.refresh_active_library_entry(inner);
+            }
+            Err(error) => self.report_error(inner, error, None),
+        }
+    }
+}
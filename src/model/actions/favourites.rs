@@ -0,0 +1,25 @@
+use crate::{mock::{self, get_library_entry}, model::{actions::Action, state::State}};
+
+impl State {
+    pub(in crate::model) fn toggle_favourite(&self, id: i32) {
+        let mut inner = self.inner.lock().unwrap();
+        let result = get_library_entry(id).and_then(|entry| mock::set_favourite(mock::real_id(id), !entry.favourite));
+        match result {
+            Ok(_) => self.refresh_active_library_entry(&mut inner),
+            Err(error) => {
+                self.report_error(&mut inner, error, Some(Action::ToggleFavourite(id)));
+            }
+        }
+    }
+
+    pub(in crate::model) fn toggle_pinned(&self, id: i32) {
+        let mut inner = self.inner.lock().unwrap();
+        let result = get_library_entry(id).and_then(|entry| mock::set_pinned(mock::real_id(id), !entry.pinned));
+        match result {
+            Ok(_) => self.refresh_active_library_entry(&mut inner),
+            Err(error) => {
+                self.report_error(&mut inner, error, Some(Action::TogglePinned(id)));
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use crate::{mock::{self, SortOrder, ViewMode}, model::{actions::Action, state::State}};
+
+impl State {
+    pub(in crate::model) fn set_view_mode(&self, id: i32, view_mode: Option<ViewMode>) {
+        let mut inner = self.inner.lock().unwrap();
+        match mock::set_view_mode(mock::real_id(id), view_mode) {
+            Ok(_) => self.refresh_active_library_entry(&mut inner),
+            Err(error) => {
+                self.report_error(&mut inner, error, Some(Action::SetViewMode(id, view_mode)));
+            }
+        }
+    }
+
+    pub(in crate::model) fn set_sort_order(&self, id: i32, sort_order: SortOrder) {
+        let mut inner = self.inner.lock().unwrap();
+        match mock::set_sort_order(mock::real_id(id), sort_order) {
+            Ok(_) => self.refresh_active_library_entry(&mut inner),
+            Err(error) => {
+                self.report_error(&mut inner, error, Some(Action::SetSortOrder(id, sort_order)));
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::{sync::atomic::Ordering, time::Duration};
+
+use chrono::Local;
+
+use crate::{
+    mock::{self, Progress},
+    model::{error::Error, state::{Field, InnerState, State}},
+};
+
+/// Long enough to not stop mid-word like a switched off radio
+const FADE_OUT: Duration = Duration::from_secs(3);
+/// A longer jump between two progress reports is a seek, not listening
+const MAX_PROGRESS_STEP: Duration = Duration::from_secs(5);
+
+impl State {
+    /// Counts what was played since the last progress report, which is real playback time unlike
+    /// the clock on the wall
+    pub(in crate::model) fn count_listening(&self, inner: &mut InnerState, progress: &Progress) {
+        let played = progress.position().checked_sub(inner.progress.position())
+            .filter(|played| *played <= MAX_PROGRESS_STEP)
+            .unwrap_or_default();
+        let may_listen = mock::record_listening(played, Local::now().naive_local());
+        // Playback that started some other way while the time is up stops right away as well
+        if !may_listen || inner.time_is_up {
+            self.stop_listening(inner);
+        }
+    }
+
+    /// Whether playback may start. Refused right here and not only faded out on the next progress
+    /// report, the remote API, the shell and the child lock's play button come through here too.
+    pub(in crate::model) fn may_start_playing(&self, inner: &mut InnerState) -> bool {
+        if inner.time_is_up || !mock::may_listen(Local::now().naive_local()) {
+            self.stop_listening(inner);
+            self.report_error(inner, Error::TimeIsUp, None);
+            return false;
+        }
+        true
+    }
+
+    /// Starts and ends the time being up with the allowed hours or a new day
+    pub(in crate::model) fn check_listening_time(&self, inner: &mut InnerState) {
+        let may_listen = mock::may_listen(Local::now().naive_local());
+        if !may_listen && !inner.time_is_up {
+            self.stop_listening(inner);
+        } else if may_listen && inner.time_is_up {
+            inner.set(Field::time_is_up(false));
+        }
+    }
+
+    pub(in crate::model) fn grant_extra_minutes(&self, pin: String, minutes: u32) {
+        let mut inner = self.inner.lock().unwrap();
+        if !self.check_pin(&mut inner, &pin) {
+            return;
+        }
+        match mock::grant_extra_minutes(minutes, Local::now().naive_local()) {
+            Ok(()) => self.check_listening_time(&mut inner),
+            Err(error) => self.report_error(&mut inner, error, None),
+        }
+    }
+
+    /// Shows that the time is up and fades out whatever is playing
+    fn stop_listening(&self, inner: &mut InnerState) {
+        if !inner.time_is_up {
+            inner.set(Field::time_is_up(true));
+        }
+        if !inner.is_playing || self.fading_out.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let (player, fading_out) = (self.player.clone(), self.fading_out.clone());
+        self.runtime.spawn(async move {
+            // Pausing reports back through the player's events like any other pause
+            if let Err(error) = player.fade_out(FADE_OUT).await {
+                eprintln!("Could not fade out: {}", error);
+            }
+            fading_out.store(false, Ordering::Relaxed);
+        });
+    }
+}
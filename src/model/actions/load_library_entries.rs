@@ -1,19 +1,56 @@
-use crate::{mock::get_library_entry, model::{actions::StateChange, state::{Field, State}}};
+use crate::{mock::{get_library_entry, scan_library, ErrorKind, ROOT_LIBRARY_ENTRY_ID}, model::{actions::Action, state::{Field, InnerState, State}}};
 
 impl State {
     pub(in crate::model) fn load_library_entry(&mut self, id: i32) {
         let mut inner = self.inner.lock().unwrap();
         match get_library_entry(id) {
-            Some(entry) => {
-                println!("Loaded library entry: {:?}", entry);
+            Ok(entry) => {
+                let mut navigation = inner.navigation.clone();
+                navigation.visit(id);
+                inner.set(Field::navigation(navigation));
                 inner.set(Field::active_library_entry(Some(entry)));
             }
-            None => {
-                println!("Library entry with id {} not found", id);
-                let mut new_messages = inner.messages.clone();
-                new_messages.push(format!("Library entry with id {} not found", id));
-                inner.set(Field::messages(new_messages));
+            Err(error) => {
+                self.report_error(&mut inner, error, Some(Action::LoadLibraryEntry(id)));
             }
         }
     }
+
+    /// The shown folder may have been renamed, refilled or deleted, a deleted one leaves for home
+    pub(in crate::model) fn library_changed(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        self.refresh_active_library_entry(&mut inner);
+
+        // A changed filter may hide entries that were found or queued before
+        let visible: Vec<i32> = scan_library().iter().map(|entry| entry.id).collect();
+        if inner.queue.iter().any(|entry| !visible.contains(&entry.id)) {
+            let queue = inner.queue.iter().filter(|entry| visible.contains(&entry.id)).cloned().collect();
+            inner.set(Field::queue(queue));
+        }
+        if inner.search_results.iter().any(|entry| !visible.contains(&entry.id)) {
+            let results = inner.search_results.iter().filter(|entry| visible.contains(&entry.id)).cloned().collect();
+            inner.set(Field::search_results(results));
+        }
+    }
+
+    /// Reloads the shown entry after the library changed underneath it, without touching the
+    /// history. If it was deleted or is hidden now, home is shown instead.
+    pub(in crate::model) fn refresh_active_library_entry(&self, inner: &mut InnerState) {
+        let Some(id) = inner.active_library_entry.as_ref().map(|entry| entry.id) else {
+            return;
+        };
+        match get_library_entry(id) {
+            Ok(entry) => inner.set(Field::active_library_entry(Some(entry))),
+            Err(error) if error.kind() == ErrorKind::NotFound => match get_library_entry(ROOT_LIBRARY_ENTRY_ID) {
+                Ok(root) => {
+                    let mut navigation = inner.navigation.clone();
+                    navigation.visit(ROOT_LIBRARY_ENTRY_ID);
+                    inner.set(Field::navigation(navigation));
+                    inner.set(Field::active_library_entry(Some(root)));
+                }
+                Err(error) => self.report_error(inner, error, Some(Action::GoHome)),
+            },
+            Err(error) => self.report_error(inner, error, Some(Action::LibraryChanged)),
+        }
+    }
 }
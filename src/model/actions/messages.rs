@@ -0,0 +1,80 @@
+use std::sync::atomic::Ordering;
+
+use chrono::Utc;
+
+use crate::model::{actions::Action, error::Error, message::Message, state::{Field, InnerState}, State};
+
+impl State {
+    pub(in crate::model) fn dismiss_message(&self, id: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        let mut messages = inner.messages.clone();
+        let count = messages.len();
+        messages.retain(|message| message.id != id);
+        if messages.len() == count {
+            return;
+        }
+        self.show_next_message(&mut inner, messages);
+    }
+
+    pub(in crate::model) fn expire_messages(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        let now = Utc::now();
+        let mut messages = inner.messages.clone();
+        if !messages.first().is_some_and(|message| message.is_expired(now)) {
+            return;
+        }
+        messages.remove(0);
+        self.show_next_message(&mut inner, messages);
+    }
+
+    /// Adds a message to the toast queue. Actions report their messages through this while they
+    /// hold the state lock. A duplicate of a queued message only bumps its counter, so a flaky
+    /// connection does not bury the screen under identical toasts.
+    pub(in crate::model) fn enqueue_message(&self, inner: &mut InnerState, mut message: Message) {
+        let mut messages = inner.messages.clone();
+        match messages.iter_mut().find(|queued| queued.is_duplicate_of(&message)) {
+            Some(queued) => {
+                queued.count += 1;
+                queued.created_at = message.created_at;
+                // Restart the countdown if the duplicate is the one on screen
+                if queued.shown_at.is_some() {
+                    queued.shown_at = None;
+                }
+            }
+            None => {
+                message.id = self.next_message_id.fetch_add(1, Ordering::Relaxed);
+                messages.push(message);
+            }
+        }
+        self.show_next_message(inner, messages);
+    }
+
+    /// Logs the technical details of an error and queues its kid friendly version. Transient
+    /// errors get a retry button if the caller knows how to retry.
+    pub(in crate::model) fn report_error(&self, inner: &mut InnerState, error: impl Into<Error>, retry: Option<Action>) {
+        let error = error.into();
+        eprintln!("Error: {}", error);
+        let mut message = Message::from(&error);
+        if let Some(retry) = retry.filter(|_| error.is_transient()) {
+            message = message.with_action("Nochmal", retry);
+        }
+        self.enqueue_message(inner, message);
+    }
+
+    /// Marks the head of the queue as shown and schedules its expiry
+    fn show_next_message(&self, inner: &mut InnerState, mut messages: Vec<Message>) {
+        if let Some(head) = messages.first_mut() {
+            if head.shown_at.is_none() {
+                head.shown_at = Some(Utc::now());
+                if let Some(ttl) = head.ttl {
+                    let state = self.clone();
+                    self.runtime.spawn(async move {
+                        tokio::time::sleep(ttl).await;
+                        state.dispatch(Action::ExpireMessages);
+                    });
+                }
+            }
+        }
+        inner.set(Field::messages(messages));
+    }
+}
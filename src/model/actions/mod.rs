@@ -1,24 +1,111 @@
-use crate::mock::{LibraryEntry, Progress};
+use std::{sync::mpsc::Sender, time::Duration};
 
+use crate::mock::{LibraryEntry, Progress, SortOrder, ViewMode};
+
+mod child_lock;
+mod edit_library;
+mod favourites;
+mod folder_preferences;
+mod listening_time;
 mod load_library_entries;
+mod messages;
+mod navigation;
+mod pin;
+mod profiles;
 mod play_library_entry;
+mod queue;
+mod search;
+mod seek;
+mod select_library_entry;
+mod set_progress;
 mod toggle_play;
+mod volume;
+
+pub use child_lock::Unlock;
+pub(in crate::model) use child_lock::random_gesture;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum Action {
     LoadLibraryEntry(i32),
+    GoBack,
+    GoForward,
+    GoHome,
+    /// Remembers how far the current entry is scrolled, so going back can restore it
+    SetScrollOffset(f32),
+    /// Stores how a folder shows its children, `None` picks the view from the children
+    SetViewMode(i32, Option<ViewMode>),
+    SetSortOrder(i32, SortOrder),
+    ToggleFavourite(i32),
+    /// Pins the entry to the home screen or removes it from there
+    TogglePinned(i32),
+    Search(String),
+    /// A tap on an entry: folders open, tracks play, the loaded track pauses or resumes
+    SelectLibraryEntry(i32),
     PlayLibraryEntry(LibraryEntry),
+    ResumeLibraryEntry(i32),
+    /// Plays from the beginning, forgetting where the entry was left off
+    RestartLibraryEntry(i32),
+    PlayNext(i32),
+    AddToQueue(i32),
+    TrackFinished,
+    /// The flag is whether the player is playing right now
     TogglePlay(bool),
-    SetProgress(Progress)
+    Seek(Duration),
+    SetProgress(Progress),
+    /// Keeps the state in sync when the player stops or resumes on its own
+    SetIsPlaying(bool),
+    DismissMessage(u64),
+    /// Drops the visible toast once its time to live ran out
+    ExpireMessages,
+    /// The library was edited from outside, reloads what is shown
+    LibraryChanged,
+    /// Unlocks editing the library if the PIN is right
+    EnterEditMode(String),
+    LeaveEditMode,
+    RenameLibraryEntry(i32, String),
+    /// Moves the entry into another folder
+    MoveLibraryEntry(i32, i32),
+    /// Moves the entry to where the second one is in the shown folder
+    ReorderLibraryEntry(i32, i32),
+    DeleteLibraryEntry(i32),
+    /// Uses an image from the images folder as the cover
+    PickCover(i32, String),
+    UndoEdit,
+    /// Between 0 and 1
+    SetVolume(f32),
+    /// Only play/pause and the volume keep working until unlocked
+    Lock,
+    Unlock(Unlock),
+    /// Runs every half minute, for everything that depends on the time of day
+    Tick,
+    /// More listening today beyond the limits, if the PIN is right
+    GrantExtraMinutes(String, u32),
+    /// Switches to the profile with this id
+    SwitchProfile(String),
+    /// Answers once every action dispatched before it has been handled
+    Sync(Sender<()>),
 }
 
-#[derive(Debug, Default, Clone)]
-pub struct StateChange {
-    pub changed_fields: Vec<String>,
-}
-
-impl StateChange {
-    pub fn new(fields: Vec<&str>) -> Self {
-        StateChange { changed_fields: fields.iter().map(|&s| s.into()).collect() }
+impl Action {
+    /// The child lock leaves the kids play/pause and the volume. Everything else, like navigating,
+    /// going back or changing settings, is dropped while locked.
+    pub fn is_allowed_while_locked(&self) -> bool {
+        matches!(
+            self,
+            Action::TogglePlay(_)
+                | Action::SetVolume(_)
+                | Action::SetProgress(_)
+                | Action::SetIsPlaying(_)
+                | Action::TrackFinished
+                | Action::DismissMessage(_)
+                | Action::ExpireMessages
+                | Action::LibraryChanged
+                | Action::Lock
+                | Action::Unlock(_)
+                | Action::Tick
+                | Action::GrantExtraMinutes(..)
+                | Action::Sync(_)
+        )
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,54 @@
+use crate::{mock::get_library_entry, model::{actions::Action, state::{Field, State}}};
+
+impl State {
+    pub(in crate::model) fn go_back(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        let mut navigation = inner.navigation.clone();
+        let parent_id = inner.active_library_entry.as_ref()
+            .and_then(|entry| entry.parent_id)
+            .filter(|parent_id| *parent_id >= 0);
+        let Some(id) = navigation.previous().map(|location| location.id).or(parent_id) else {
+            return;
+        };
+
+        match get_library_entry(id) {
+            Ok(entry) => {
+                if navigation.go_back().is_none() {
+                    navigation.go_up(id);
+                }
+                inner.set(Field::navigation(navigation));
+                inner.set(Field::active_library_entry(Some(entry)));
+            }
+            Err(error) => {
+                self.report_error(&mut inner, error, Some(Action::GoBack));
+            }
+        }
+    }
+
+    pub(in crate::model) fn go_forward(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        let mut navigation = inner.navigation.clone();
+        let Some(next) = navigation.next() else {
+            return;
+        };
+
+        match get_library_entry(next.id) {
+            Ok(entry) => {
+                navigation.go_forward();
+                inner.set(Field::navigation(navigation));
+                inner.set(Field::active_library_entry(Some(entry)));
+            }
+            Err(error) => {
+                self.report_error(&mut inner, error, Some(Action::GoForward));
+            }
+        }
+    }
+
+    pub(in crate::model) fn set_scroll_offset(&self, scroll_offset: f32) {
+        let mut inner = self.inner.lock().unwrap();
+        let mut navigation = inner.navigation.clone();
+        navigation.set_scroll_offset(scroll_offset);
+        // Only bookkeeping, nobody needs to be notified about this
+        inner.navigation = navigation;
+    }
+}
@@ -0,0 +1,13 @@
+use crate::model::{error::Error, state::{InnerState, State}};
+
+impl State {
+    /// Whether `pin` unlocks the parents' features, a wrong or missing PIN is reported
+    pub(in crate::model) fn check_pin(&self, inner: &mut InnerState, pin: &str) -> bool {
+        match self.pin.as_deref() {
+            None => self.report_error(inner, Error::NoPin, None),
+            Some(expected) if expected != pin => self.report_error(inner, Error::WrongPin, None),
+            Some(_) => return true,
+        }
+        false
+    }
+}
@@ -1,24 +1,39 @@
-use crate::{mock::LibraryEntry, model::{actions::StateChange, state::Field, State}};
+use crate::{mock::{mark_played, LibraryEntry}, model::{actions::Action, state::Field, State}};
 
 
 impl State {
+  /// Plays the entry, continuing where it was left off last time
   pub(in crate::model) fn play_library_entry(&self, library_entry: LibraryEntry) {
     let player = self.player.clone();
     let state = self.clone();
+    let resume_position = {
+      let mut inner = self.inner.lock().unwrap();
+      if !self.may_start_playing(&mut inner) {
+        return;
+      }
+      inner.resume_positions.get(&library_entry.id).cloned()
+    };
 
-    tokio::runtime::Handle::current().block_on(async move {
-      let result = player.play(library_entry.clone()).await;
+    self.runtime.block_on(async move {
+      let mut result = player.play(library_entry.clone()).await;
+      if let (Ok(_), Some(progress)) = (&result, &resume_position) {
+        result = player.seek(progress.position()).await;
+      }
       let mut inner = state.inner.lock().unwrap();
 
       match result {
         Ok(_) => {
+          // Losing the date is not worth interrupting playback for
+          if let Err(error) = mark_played(library_entry.id, chrono::Utc::now()) {
+            eprintln!("Could not mark library entry {} as played: {}", library_entry.id, error);
+          }
+          state.refresh_active_library_entry(&mut inner);
           inner.set(Field::playing_library_entry(Some(library_entry)));
+          inner.set(Field::progress(resume_position.unwrap_or_default()));
           inner.set(Field::is_playing(true));
         }
         Err(error) => {
-          let mut new_messages = inner.messages.clone();
-          new_messages.push(format!("Could not play track: {}", error));
-          inner.set(Field::messages(new_messages));
+          state.report_error(&mut inner, error, Some(Action::PlayLibraryEntry(library_entry)));
         }
       }
     })
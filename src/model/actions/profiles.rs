@@ -0,0 +1,65 @@
+use std::sync::atomic::Ordering;
+
+use crate::{
+    library_store::Session,
+    mock::{self, LibraryError, Progress, ROOT_LIBRARY_ENTRY_ID},
+    model::{navigation::Navigation, state::{Field, InnerState, State}},
+};
+
+impl State {
+    /// Nothing of the previous profile carries over: playback stops, history and queue start over
+    /// and the other profile's favourites, resume points and limits take effect.
+    pub(in crate::model) fn switch_profile(&mut self, id: String) {
+        let Some(profile) = self.profiles.iter().find(|profile| profile.id == id).cloned() else {
+            let mut inner = self.inner.lock().unwrap();
+            return self.report_error(&mut inner, LibraryError::Invalid(format!("unknown profile {}", id)), None);
+        };
+        {
+            let inner = self.inner.lock().unwrap();
+            if inner.profile.as_ref() == Some(&profile) {
+                return;
+            }
+            self.save_session(&inner);
+        }
+
+        let player = self.player.clone();
+        if let Err(error) = self.runtime.block_on(player.stop()) {
+            eprintln!("Could not stop playback for the profile switch: {}", error);
+        }
+        let session = mock::switch_profile(Some(profile.clone()));
+        let volume = session.volume.unwrap_or_else(|| player.volume()).min(mock::parental_limits().loudest());
+        if let Err(error) = self.runtime.block_on(player.set_volume(volume)) {
+            eprintln!("Could not restore the volume of {}: {}", profile.name, error);
+        }
+
+        {
+            let mut inner = self.inner.lock().unwrap();
+            inner.set(Field::profile(Some(profile)));
+            inner.set(Field::playing_library_entry(None));
+            inner.set(Field::is_playing(false));
+            inner.set(Field::progress(Progress::default()));
+            inner.set(Field::queue(vec![]));
+            inner.set(Field::resume_positions(session.resume_positions.into_iter().collect()));
+            inner.set(Field::volume(volume));
+            inner.set(Field::search_query(String::new()));
+            inner.set(Field::search_results(vec![]));
+            inner.set(Field::navigation(Navigation::default()));
+            if inner.edit_mode {
+                inner.set(Field::edit_mode(false));
+            }
+            // The other profile may have its own lock schedule and surely has its own budget
+            self.lock_scheduled.store(false, Ordering::Relaxed);
+            self.check_lock_schedule(&mut inner);
+            self.check_listening_time(&mut inner);
+        }
+        self.load_library_entry(ROOT_LIBRARY_ENTRY_ID);
+    }
+
+    /// Keeps where listening left off, for the next start or switching back
+    pub(in crate::model) fn save_session(&self, inner: &InnerState) {
+        mock::save_session(Session {
+            resume_positions: inner.resume_positions.iter().map(|(id, progress)| (*id, progress.clone())).collect(),
+            volume: Some(inner.volume),
+        });
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{mock::{self, get_library_entry, Variant}, model::{actions::Action, state::{Field, State}}};
+
+impl State {
+    pub(in crate::model) fn play_next(&self, id: i32) {
+        let mut inner = self.inner.lock().unwrap();
+        match get_library_entry(id) {
+            Ok(entry) => {
+                let mut queue = inner.queue.clone();
+                queue.retain(|queued| queued.id != id);
+                queue.insert(0, entry);
+                inner.set(Field::queue(queue));
+            }
+            Err(error) => {
+                self.report_error(&mut inner, error, Some(Action::PlayNext(id)));
+            }
+        }
+    }
+
+    pub(in crate::model) fn add_to_queue(&self, id: i32) {
+        let mut inner = self.inner.lock().unwrap();
+        match get_library_entry(id) {
+            Ok(entry) => {
+                let mut queue = inner.queue.clone();
+                queue.push(entry);
+                inner.set(Field::queue(queue));
+            }
+            Err(error) => {
+                self.report_error(&mut inner, error, Some(Action::AddToQueue(id)));
+            }
+        }
+    }
+
+    /// Forgets where the finished entry was left off and moves on to the next queued one
+    pub(in crate::model) fn track_finished(&self) {
+        let next = {
+            let mut inner = self.inner.lock().unwrap();
+            if let Some(id) = inner.playing_library_entry.as_ref().map(|entry| entry.id) {
+                let mut resume_positions = inner.resume_positions.clone();
+                if resume_positions.remove(&id).is_some() {
+                    inner.set(Field::resume_positions(resume_positions));
+                }
+            }
+            // Listened to the end, the download has done its job
+            if let Some(episode) = inner.playing_library_entry.as_ref().filter(|entry| entry.variant == Variant::Episode) {
+                match mock::episode_played(episode.id) {
+                    Ok(()) => self.refresh_active_library_entry(&mut inner),
+                    Err(error) => eprintln!("Could not mark episode {} as played: {}", episode.id, error),
+                }
+            }
+
+            let mut queue = inner.queue.clone();
+            if queue.is_empty() {
+                inner.set(Field::is_playing(false));
+                return;
+            }
+            let next = queue.remove(0);
+            inner.set(Field::queue(queue));
+            next
+        };
+        self.play_library_entry(next);
+    }
+}
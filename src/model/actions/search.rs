@@ -0,0 +1,18 @@
+use crate::{mock::search_library, model::{actions::Action, state::{Field, State}}};
+
+impl State {
+    pub(in crate::model) fn search(&self, query: String) {
+        let mut inner = self.inner.lock().unwrap();
+        // An empty query shows nothing rather than the whole library
+        let results = if query.trim().is_empty() { Ok(vec![]) } else { search_library(&query) };
+        match results {
+            Ok(results) => {
+                inner.set(Field::search_query(query));
+                inner.set(Field::search_results(results));
+            }
+            Err(error) => {
+                self.report_error(&mut inner, error, Some(Action::Search(query)));
+            }
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+use crate::model::{actions::Action, state::Field, State};
+
+
+impl State {
+  pub(in crate::model) fn seek(&self, position: Duration) {
+    let player = self.player.clone();
+    let state = self.clone();
+
+    self.runtime.block_on(async move {
+      let result = player.seek(position).await;
+
+      let mut inner = state.inner.lock().unwrap();
+      match result {
+        Ok(_) => {
+          let progress = inner.progress.with_position(position);
+          inner.set(Field::progress(progress));
+        }
+        Err(error) => {
+          state.report_error(&mut inner, error, Some(Action::Seek(position)));
+        }
+      }
+    })
+  }
+}
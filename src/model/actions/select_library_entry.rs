@@ -0,0 +1,62 @@
+use crate::{mock::{get_library_entry, Variant}, model::{actions::Action, state::{Field, State}}};
+
+impl State {
+    pub(in crate::model) fn select_library_entry(&mut self, id: i32) {
+        let (loaded_id, is_playing) = {
+            let inner = self.inner.lock().unwrap();
+            (inner.playing_library_entry.as_ref().map(|entry| entry.id), inner.is_playing)
+        };
+        if loaded_id == Some(id) {
+            self.toggle_play(is_playing);
+            return;
+        }
+
+        let entry = match get_library_entry(id) {
+            Ok(entry) => entry,
+            Err(error) => {
+                let mut inner = self.inner.lock().unwrap();
+                self.report_error(&mut inner, error, Some(Action::SelectLibraryEntry(id)));
+                return;
+            }
+        };
+        match entry.variant {
+            Variant::Folder | Variant::Podcast => self.load_library_entry(id),
+            Variant::Stream | Variant::File | Variant::Spotify | Variant::Episode => self.play_library_entry(entry),
+        }
+    }
+
+    pub(in crate::model) fn resume_library_entry(&mut self, id: i32) {
+        let (loaded_id, is_playing) = {
+            let inner = self.inner.lock().unwrap();
+            (inner.playing_library_entry.as_ref().map(|entry| entry.id), inner.is_playing)
+        };
+        match loaded_id {
+            Some(loaded_id) if loaded_id == id && is_playing => {}
+            Some(loaded_id) if loaded_id == id => self.toggle_play(false),
+            _ => match get_library_entry(id) {
+                Ok(entry) => self.play_library_entry(entry),
+                Err(error) => {
+                    let mut inner = self.inner.lock().unwrap();
+                    self.report_error(&mut inner, error, Some(Action::ResumeLibraryEntry(id)));
+                }
+            },
+        }
+    }
+
+    pub(in crate::model) fn restart_library_entry(&mut self, id: i32) {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            let mut resume_positions = inner.resume_positions.clone();
+            if resume_positions.remove(&id).is_some() {
+                inner.set(Field::resume_positions(resume_positions));
+            }
+        }
+        match get_library_entry(id) {
+            Ok(entry) => self.play_library_entry(entry),
+            Err(error) => {
+                let mut inner = self.inner.lock().unwrap();
+                self.report_error(&mut inner, error, Some(Action::RestartLibraryEntry(id)));
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use crate::{mock::Progress, model::{state::Field, State}};
+
+impl State {
+    pub(in crate::model) fn set_progress(&self, progress: Progress) {
+        let mut inner = self.inner.lock().unwrap();
+        self.count_listening(&mut inner, &progress);
+        if let Some(id) = inner.playing_library_entry.as_ref().map(|entry| entry.id) {
+            // Only bookkeeping for the next time the entry is played, nobody watches this live
+            inner.resume_positions.insert(id, progress.clone());
+        }
+        inner.set(Field::progress(progress));
+    }
+
+    pub(in crate::model) fn set_is_playing(&self, is_playing: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.is_playing != is_playing {
+            inner.set(Field::is_playing(is_playing));
+        }
+        if !is_playing {
+            self.save_session(&inner);
+        }
+    }
+}
@@ -1,14 +1,16 @@
-use tokio::runtime::Handle;
-
-use crate::model::{state::Field, State};
+use crate::model::{actions::Action, state::Field, State};
 
 
 impl State {
+  /// `is_playing` is what the caller saw, playing pauses and paused resumes
   pub(in crate::model) fn toggle_play(&self, is_playing: bool) {
+    if !is_playing && !self.may_start_playing(&mut self.inner.lock().unwrap()) {
+      return;
+    }
     let player = self.player.clone();
     let state = self.clone();
 
-    Handle::current().block_on(async move {
+    self.runtime.block_on(async move {
       let result = if is_playing {
         player.pause().await
       } else {
@@ -18,12 +20,10 @@ impl State {
       let mut inner = state.inner.lock().unwrap();
       match result {
         Ok(_) => {
-          inner.set(Field::is_playing(is_playing));
+          inner.set(Field::is_playing(!is_playing));
         }
         Err(error) => {
-          let mut new_messages = inner.messages.clone();
-          new_messages.push(format!("Could not toggle play: {}", error));
-          inner.set(Field::messages(new_messages));
+          state.report_error(&mut inner, error, Some(Action::TogglePlay(is_playing)));
         }
       }
     })
@@ -0,0 +1,31 @@
+use crate::{mock, model::{actions::Action, state::{Field, InnerState}, State}};
+
+impl State {
+    /// Between 0 and 1, and no louder than the active profile's limits allow
+    pub(in crate::model) fn set_volume(&self, volume: f32) {
+        let volume = volume.clamp(0.0, 1.0).min(mock::parental_limits().loudest());
+        let player = self.player.clone();
+        let result = self.runtime.block_on(player.set_volume(volume));
+
+        let mut inner = self.inner.lock().unwrap();
+        match result {
+            Ok(()) => inner.set(Field::volume(volume)),
+            Err(error) => self.report_error(&mut inner, error, Some(Action::SetVolume(volume))),
+        }
+    }
+
+    /// Turns down what became too loud, the parents may have lowered the limit meanwhile
+    pub(in crate::model) fn check_max_volume(&self, inner: &mut InnerState) {
+        let loudest = mock::parental_limits().loudest();
+        if inner.volume <= loudest {
+            return;
+        }
+        let player = self.player.clone();
+        self.runtime.spawn(async move {
+            if let Err(error) = player.set_volume(loudest).await {
+                eprintln!("Could not turn the volume down to the limit: {}", error);
+            }
+        });
+        inner.set(Field::volume(loudest));
+    }
+}
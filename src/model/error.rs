@@ -0,0 +1,92 @@
+use std::fmt::Display;
+
+use crate::mock::{ErrorKind, LibraryError, PlayerError};
+
+use super::message::{Message, Severity};
+
+/// Everything an action can fail with. `Display` is the technical description for the logs,
+/// `user_text` is what the kids get to read.
+#[derive(Clone, Debug)]
+pub enum Error {
+    Player(PlayerError),
+    Library(LibraryError),
+    /// The PIN typed for the parents' features was wrong
+    WrongPin,
+    /// The parents' features were asked for, but no PIN is configured
+    NoPin,
+    /// The daily budget ran out or it is outside the allowed hours
+    TimeIsUp,
+}
+
+impl Error {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Player(error) => error.kind(),
+            Error::Library(error) => error.kind(),
+            Error::WrongPin | Error::NoPin | Error::TimeIsUp => ErrorKind::Invalid,
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self.kind() {
+            ErrorKind::Busy => Severity::Info,
+            ErrorKind::NotFound | ErrorKind::Invalid => Severity::Warning,
+            ErrorKind::Decode | ErrorKind::Network => Severity::Error,
+        }
+    }
+
+    /// The single place where errors are turned into text for the screen
+    pub fn user_text(&self) -> String {
+        match self {
+            Error::Player(PlayerError::NoTrackLoaded) => "Such dir zuerst etwas zum Anhören aus.".to_string(),
+            Error::Player(PlayerError::NoSource(_)) => "Das kann man nicht abspielen.".to_string(),
+            Error::WrongPin => "Die PIN stimmt nicht.".to_string(),
+            Error::NoPin => "Dafür muss erst eine PIN eingerichtet werden.".to_string(),
+            Error::TimeIsUp => "Für heute ist die Hörzeit um.".to_string(),
+            _ => match self.kind() {
+                ErrorKind::NotFound => "Das ist leider nicht mehr da.".to_string(),
+                ErrorKind::Decode => "Das klingt kaputt, versuch etwas anderes.".to_string(),
+                ErrorKind::Network => "Keine Verbindung. Ist das WLAN an?".to_string(),
+                ErrorKind::Busy => "Einen kleinen Moment bitte …".to_string(),
+                ErrorKind::Invalid => "Das geht so nicht.".to_string(),
+            },
+        }
+    }
+
+    /// Whether trying the same thing again might help
+    pub fn is_transient(&self) -> bool {
+        matches!(self.kind(), ErrorKind::Network | ErrorKind::Busy)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Player(error) => write!(f, "player: {}", error),
+            Error::Library(error) => write!(f, "library: {}", error),
+            Error::WrongPin => write!(f, "wrong pin"),
+            Error::NoPin => write!(f, "no pin configured"),
+            Error::TimeIsUp => write!(f, "listening time is up"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<PlayerError> for Error {
+    fn from(error: PlayerError) -> Self {
+        Error::Player(error)
+    }
+}
+
+impl From<LibraryError> for Error {
+    fn from(error: LibraryError) -> Self {
+        Error::Library(error)
+    }
+}
+
+impl From<&Error> for Message {
+    fn from(error: &Error) -> Self {
+        Message::new(error.severity(), error.user_text())
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use super::actions::Action;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// How long a toast of this severity stays visible if nobody dismisses it
+    pub fn default_ttl(&self) -> Option<Duration> {
+        match self {
+            Severity::Info => Some(Duration::from_secs(4)),
+            Severity::Warning => Some(Duration::from_secs(8)),
+            // Errors stay until the user taps them away
+            Severity::Error => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MessageAction {
+    pub label: String,
+    pub action: Box<Action>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Message {
+    pub id: u64,
+    pub severity: Severity,
+    pub text: String,
+    pub created_at: DateTime<Utc>,
+    /// Set once the message reaches the front of the toast queue, the TTL counts from here
+    pub shown_at: Option<DateTime<Utc>>,
+    pub ttl: Option<Duration>,
+    pub action: Option<MessageAction>,
+    /// How often the same message was pushed while it was still queued
+    pub count: u32,
+}
+
+impl Message {
+    pub fn new(severity: Severity, text: impl Into<String>) -> Self {
+        Message {
+            id: 0,
+            severity,
+            text: text.into(),
+            created_at: Utc::now(),
+            shown_at: None,
+            ttl: severity.default_ttl(),
+            action: None,
+            count: 1,
+        }
+    }
+
+    pub fn with_action(mut self, label: impl Into<String>, action: Action) -> Self {
+        self.action = Some(MessageAction { label: label.into(), action: Box::new(action) });
+        self
+    }
+
+    /// Two messages are duplicates if the user could not tell them apart
+    pub fn is_duplicate_of(&self, other: &Message) -> bool {
+        self.severity == other.severity && self.text == other.text
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        match (self.shown_at, self.ttl) {
+            (Some(shown_at), Some(ttl)) => shown_at + ttl <= now,
+            _ => false,
+        }
+    }
+}
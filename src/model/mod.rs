@@ -1,7 +1,12 @@
 pub mod actions;
+mod error;
+mod message;
+mod navigation;
 mod state;
+mod undo;
 mod util;
 
 pub use actions::Action;
+pub use message::{Message, Severity};
 pub use state::State;
 pub use state::Field;
@@ -0,0 +1,151 @@
+/// A visited library entry together with how far its list was scrolled
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Location {
+    pub id: i32,
+    /// Vertical viewport offset of the content `ScrollView`, as reported by the UI
+    pub scroll_offset: f32,
+}
+
+impl Location {
+    pub fn new(id: i32) -> Self {
+        Location { id, scroll_offset: 0.0 }
+    }
+}
+
+/// Browser like history of visited library entries
+#[derive(Clone, Debug, Default)]
+pub struct Navigation {
+    pub back: Vec<Location>,
+    pub current: Option<Location>,
+    pub forward: Vec<Location>,
+}
+
+impl Navigation {
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+
+    /// Records a newly opened entry, which drops the forward history like a browser does
+    pub fn visit(&mut self, id: i32) {
+        if self.current.is_some_and(|current| current.id == id) {
+            return;
+        }
+        if let Some(current) = self.current.take() {
+            self.back.push(current);
+        }
+        self.forward.clear();
+        self.current = Some(Location::new(id));
+    }
+
+    /// Peeks at the location `go_back` would return to
+    pub fn previous(&self) -> Option<Location> {
+        self.back.last().copied()
+    }
+
+    pub fn next(&self) -> Option<Location> {
+        self.forward.last().copied()
+    }
+
+    pub fn go_back(&mut self) -> Option<Location> {
+        let previous = self.back.pop()?;
+        if let Some(current) = self.current.replace(previous) {
+            self.forward.push(current);
+        }
+        Some(previous)
+    }
+
+    pub fn go_forward(&mut self) -> Option<Location> {
+        let next = self.forward.pop()?;
+        if let Some(current) = self.current.replace(next) {
+            self.back.push(current);
+        }
+        Some(next)
+    }
+
+    /// Steps up to the parent when there is no history to go back to, e.g. after a deep link
+    pub fn go_up(&mut self, parent_id: i32) {
+        if let Some(current) = self.current.replace(Location::new(parent_id)) {
+            self.forward.push(current);
+        }
+    }
+
+    pub fn set_scroll_offset(&mut self, scroll_offset: f32) {
+        if let Some(current) = self.current.as_mut() {
+            current.scroll_offset = scroll_offset;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn visited(ids: &[i32]) -> Navigation {
+        let mut navigation = Navigation::default();
+        for id in ids {
+            navigation.visit(*id);
+        }
+        navigation
+    }
+
+    fn current(navigation: &Navigation) -> Option<i32> {
+        navigation.current.map(|location| location.id)
+    }
+
+    #[test]
+    fn goes_back_and_forward_again() {
+        let mut navigation = visited(&[0, 1, 2]);
+
+        assert_eq!(navigation.go_back().map(|location| location.id), Some(1));
+        assert_eq!(navigation.go_back().map(|location| location.id), Some(0));
+        assert_eq!(navigation.go_back(), None);
+        assert_eq!(current(&navigation), Some(0));
+
+        assert!(navigation.can_go_forward());
+        assert_eq!(navigation.next().map(|location| location.id), Some(1));
+        assert_eq!(navigation.go_forward().map(|location| location.id), Some(1));
+        assert_eq!(navigation.go_forward().map(|location| location.id), Some(2));
+        assert_eq!(navigation.go_forward(), None);
+        assert!(!navigation.can_go_forward());
+        assert_eq!(navigation.previous().map(|location| location.id), Some(1));
+    }
+
+    #[test]
+    fn visiting_drops_the_forward_history() {
+        let mut navigation = visited(&[0, 1, 2]);
+        navigation.go_back();
+        navigation.visit(3);
+
+        assert!(!navigation.can_go_forward());
+        assert_eq!(navigation.go_back().map(|location| location.id), Some(1));
+    }
+
+    #[test]
+    fn visiting_the_current_entry_again_adds_nothing() {
+        let mut navigation = visited(&[0, 1, 1]);
+
+        assert_eq!(navigation.back.len(), 1);
+        assert_eq!(navigation.go_back().map(|location| location.id), Some(0));
+    }
+
+    #[test]
+    fn keeps_the_scroll_offset_of_each_location() {
+        let mut navigation = visited(&[0]);
+        navigation.set_scroll_offset(-120.0);
+        navigation.visit(1);
+        navigation.set_scroll_offset(-40.0);
+
+        assert_eq!(navigation.go_back(), Some(Location { id: 0, scroll_offset: -120.0 }));
+        assert_eq!(navigation.go_forward(), Some(Location { id: 1, scroll_offset: -40.0 }));
+    }
+
+    #[test]
+    fn goes_up_without_history() {
+        let mut navigation = visited(&[5]);
+        navigation.go_up(2);
+
+        assert_eq!(current(&navigation), Some(2));
+        assert_eq!(navigation.go_back(), None);
+        assert_eq!(navigation.go_forward().map(|location| location.id), Some(5));
+    }
+}
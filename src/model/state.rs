@@ -1,66 +1,203 @@
-use super::actions::{Action, StateChange};
-use crate::{mock::{LibraryEntry, Player, Progress}, with_getters_setters};
+use super::actions::Action;
+use super::message::Message;
+use super::navigation::Navigation;
+use super::undo::UndoStep;
+use crate::{mock::{self, LibraryEntry, Player, Profile, Progress, ROOT_LIBRARY_ENTRY_ID}, with_getters_setters};
+use std::collections::HashMap;
+use std::time::Duration;
 use std::sync::{
+    atomic::{AtomicBool, AtomicU64},
     mpsc::{channel, Sender},
     Arc, Mutex,
 };
+use tokio::runtime::Handle;
 
 type Changes = Vec<Field>;
 
+const TICK_INTERVAL: Duration = Duration::from_secs(30);
+
 with_getters_setters! {
     #[derive(Default)]
     pub struct InnerState {
-        /// List of library entries
-        pub messages: Vec<String>,
+        /// Toast queue, the first message is the one currently shown
+        pub messages: Vec<Message>,
         pub active_library_entry: Option<LibraryEntry>,
+        pub navigation: Navigation,
         pub playing_library_entry: Option<LibraryEntry>,
         pub is_playing: bool,
         pub progress: Progress,
+        /// Entries played next, in order
+        pub queue: Vec<LibraryEntry>,
+        /// Where each entry was left off, keyed by library entry id
+        pub resume_positions: HashMap<i32, Progress>,
+        pub search_query: String,
+        /// Matches for `search_query`, best first
+        pub search_results: Vec<LibraryEntry>,
+        /// The parents unlocked editing the library on the device
+        pub edit_mode: bool,
+        /// Edits of this session, the last one is undone first
+        pub undo_stack: Vec<UndoStep>,
+        /// Between 0 and 1
+        pub volume: f32,
+        /// The child lock is on, only play/pause and the volume work
+        pub locked: bool,
+        /// The daily budget ran out or it is outside the allowed hours
+        pub time_is_up: bool,
+        /// Who is listening, `None` until a profile was picked or when none are set up
+        pub profile: Option<Profile>,
     }
 
     pub struct State {
         subscribers: Arc<Mutex<Vec<Sender<Changes>>>>,
         action_tx: Arc<Mutex<Sender<Action>>>,
         pub(super) player: Player,
+        pub(super) runtime: Handle,
+        pub(super) next_message_id: Arc<AtomicU64>,
+        /// Unlocks the parents' features, `None` keeps them locked
+        pub(super) pin: Option<Arc<str>>,
+        /// Whether the last tick was within the scheduled lock time
+        pub(super) lock_scheduled: Arc<AtomicBool>,
+        /// Playback is being faded out because the time is up
+        pub(super) fading_out: Arc<AtomicBool>,
+        /// Every profile there is to pick from
+        pub(super) profiles: Arc<[Profile]>,
+        /// Unlocks the child lock without the PIN, corners as in `Unlock::Gesture`
+        pub(super) unlock_gesture: Arc<[u8]>,
     }
 }
 
 impl State {
-    pub fn new(player: Player) -> Self {
+    /// Must be called from within a tokio runtime, actions run on a dedicated thread which uses
+    /// this runtime to drive the player.
+    pub fn new(player: Player, pin: Option<String>, profiles: Vec<Profile>) -> Self {
         let (tx, rx) = channel::<Action>();
         let subscribers = Arc::new(Mutex::new(Vec::new()));
+        // Until a profile is picked, this is the session of the one used without profiles
+        let session = mock::session();
+        let volume = session.volume.unwrap_or_else(|| player.volume()).min(mock::parental_limits().loudest());
         let self_ = Self {
-            inner: Arc::new(Mutex::new(InnerState::default())),
+            inner: Arc::new(Mutex::new(InnerState {
+                volume,
+                resume_positions: session.resume_positions.into_iter().collect(),
+                ..Default::default()
+            })),
             subscribers,
             action_tx: Arc::new(Mutex::new(tx)),
-            player
+            player,
+            runtime: Handle::current(),
+            next_message_id: Arc::new(AtomicU64::new(1)),
+            pin: pin.filter(|pin| !pin.is_empty()).map(Arc::from),
+            lock_scheduled: Arc::new(AtomicBool::new(false)),
+            fading_out: Arc::new(AtomicBool::new(false)),
+            profiles: profiles.into(),
+            unlock_gesture: super::actions::random_gesture(),
         };
 
         {
             let mut self_clone = self_.clone();
-            tokio::spawn(async move {
+            std::thread::spawn(move || {
                 for action in rx {
-                    println!("Received action: {:?}", action);
+                    // Enforced here and not only in the UI, the remote API and the shell go through here too
+                    if self_clone.inner.lock().unwrap().locked && !action.is_allowed_while_locked() {
+                        eprintln!("Ignoring action while locked");
+                        continue;
+                    }
                     match action {
                         Action::LoadLibraryEntry(id) => self_clone.load_library_entry(id),
+                        Action::GoBack => self_clone.go_back(),
+                        Action::GoForward => self_clone.go_forward(),
+                        Action::GoHome => self_clone.load_library_entry(ROOT_LIBRARY_ENTRY_ID),
+                        Action::SetScrollOffset(scroll_offset) => self_clone.set_scroll_offset(scroll_offset),
+                        Action::SetViewMode(id, view_mode) => self_clone.set_view_mode(id, view_mode),
+                        Action::SetSortOrder(id, sort_order) => self_clone.set_sort_order(id, sort_order),
+                        Action::ToggleFavourite(id) => self_clone.toggle_favourite(id),
+                        Action::TogglePinned(id) => self_clone.toggle_pinned(id),
+                        Action::Search(query) => self_clone.search(query),
+                        Action::SelectLibraryEntry(id) => self_clone.select_library_entry(id),
                         Action::PlayLibraryEntry(library_entry) => self_clone.play_library_entry(library_entry),
+                        Action::ResumeLibraryEntry(id) => self_clone.resume_library_entry(id),
+                        Action::RestartLibraryEntry(id) => self_clone.restart_library_entry(id),
+                        Action::PlayNext(id) => self_clone.play_next(id),
+                        Action::AddToQueue(id) => self_clone.add_to_queue(id),
+                        Action::TrackFinished => self_clone.track_finished(),
                         Action::TogglePlay(is_playing) => self_clone.toggle_play(is_playing),
-                        Action::SetProgress(progress) => todo!(),
+                        Action::Seek(position) => self_clone.seek(position),
+                        Action::SetProgress(progress) => self_clone.set_progress(progress),
+                        Action::SetIsPlaying(is_playing) => self_clone.set_is_playing(is_playing),
+                        Action::DismissMessage(id) => self_clone.dismiss_message(id),
+                        Action::ExpireMessages => self_clone.expire_messages(),
+                        Action::LibraryChanged => self_clone.library_changed(),
+                        Action::EnterEditMode(pin) => self_clone.enter_edit_mode(pin),
+                        Action::LeaveEditMode => self_clone.leave_edit_mode(),
+                        Action::RenameLibraryEntry(id, name) => self_clone.rename_library_entry(id, name),
+                        Action::MoveLibraryEntry(id, parent_id) => self_clone.move_library_entry(id, parent_id),
+                        Action::ReorderLibraryEntry(id, target_id) => self_clone.reorder_library_entry(id, target_id),
+                        Action::DeleteLibraryEntry(id) => self_clone.delete_library_entry(id),
+                        Action::PickCover(id, name) => self_clone.pick_cover(id, name),
+                        Action::UndoEdit => self_clone.undo_edit(),
+                        Action::SetVolume(volume) => self_clone.set_volume(volume),
+                        Action::Lock => self_clone.lock(),
+                        Action::Unlock(unlock) => self_clone.unlock(unlock),
+                        Action::Tick => self_clone.tick(),
+                        Action::GrantExtraMinutes(pin, minutes) => self_clone.grant_extra_minutes(pin, minutes),
+                        Action::SwitchProfile(id) => self_clone.switch_profile(id),
+                        Action::Sync(done) => {
+                            done.send(()).ok();
+                        }
                     };
 
                     // Notify subscribers about the state change
-                    let changes = self_clone.inner.lock().unwrap().changes.clone();
+                    let changes = std::mem::take(&mut self_clone.inner.lock().unwrap().changes);
+                    if changes.is_empty() {
+                        continue;
+                    }
                     let subs = self_clone.subscribers.lock().unwrap();
                     for sub in subs.iter() {
-                        sub.send(changes.clone()).unwrap();
+                        sub.send(changes.clone()).ok();
                     }
                 }
             });
         }
 
+        {
+            let player = self_.player.clone();
+            self_.runtime.spawn(async move {
+                if let Err(error) = player.set_volume(volume).await {
+                    eprintln!("Could not restore the volume: {}", error);
+                }
+            });
+        }
+
+        {
+            let state = self_.clone();
+            self_.runtime.spawn(async move {
+                let mut interval = tokio::time::interval(TICK_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    state.dispatch(Action::Tick);
+                }
+            });
+        }
+
         self_
     }
 
+    pub fn profiles(&self) -> Vec<Profile> {
+        self.profiles.to_vec()
+    }
+
+    /// For parents' features outside the device, like the remote's admin routes, which check it
+    /// themselves without a toast on the device
+    pub fn pin(&self) -> Option<Arc<str>> {
+        self.pin.clone()
+    }
+
+    /// Only for where the parents look, the edit bar and the admin page. Not a field, those reach
+    /// every remote with just the token.
+    pub fn unlock_gesture(&self) -> Vec<u8> {
+        self.unlock_gesture.to_vec()
+    }
+
     pub fn dispatch(&self, action: Action) {
         let tx = self.action_tx.lock().unwrap();
         tx.send(action).unwrap();
@@ -0,0 +1,9 @@
+use crate::mock::EntryBackup;
+
+/// One edit of the library, with what it changed backed up so it can be taken back
+#[derive(Clone, Debug)]
+pub struct UndoStep {
+    /// What the edit did, shown on the undo button
+    pub label: String,
+    pub(super) backup: Vec<EntryBackup>,
+}
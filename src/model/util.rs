@@ -27,7 +27,8 @@ macro_rules! with_getters_setters {
             )*
         }
 
-        #[derive(Clone)]
+        #[derive(Clone, Debug)]
+        #[allow(non_camel_case_types)]
         $outer_vis enum Field {
             $(
                 $field($ty),
@@ -64,6 +65,12 @@ macro_rules! with_getters_setters {
                     self.inner.lock().unwrap().$field.clone()
                 }
             )*
+
+            /// Every field with its current value, all taken under the same lock
+            pub fn fields(&self) -> Vec<Field> {
+                let inner = self.inner.lock().unwrap();
+                vec![$(Field::$field(inner.$field.clone()),)*]
+            }
         }
     };
 }
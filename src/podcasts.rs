@@ -0,0 +1,385 @@
+//! Podcast subscriptions. Feeds are fetched over HTTP, their episodes become entries of the
+//! podcast and are downloaded in the background, newest first, as far as the storage quota goes.
+//!
+//! Nothing here needs the internet. The tests serve the fixtures from localhost, and so can anyone
+//! trying it from the shell:
+//!
+//! ```text
+//! python3 -m http.server 8000 --directory fixtures/podcasts
+//! cargo run -- --headless
+//! > podcast add http://127.0.0.1:8000/rss.xml
+//! > podcast refresh
+//! ```
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use roxmltree::{Document, Node};
+use tokio::{io::AsyncWriteExt, sync::Notify};
+
+use crate::{
+    config::PodcastConfig,
+    library_store::{self, StoredEpisode},
+    mock::{self, LibraryError},
+    model::{Action, State},
+};
+
+/// Feeds that answer slower than this are tried again on the next refresh
+const FEED_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A feed as far as the player cares
+#[derive(Debug)]
+pub struct Feed {
+    pub title: String,
+    /// In the order the feed lists them
+    pub episodes: Vec<FeedEpisode>,
+}
+
+#[derive(Debug)]
+pub struct FeedEpisode {
+    pub guid: String,
+    pub title: String,
+    /// The enclosure, already resolved against the feed's URL
+    pub url: String,
+    pub published: Option<DateTime<Utc>>,
+    /// Bytes the feed announced, 0 when it did not say
+    pub length: u64,
+}
+
+/// How far a download got
+enum Download {
+    /// The file name and its size
+    Complete(String, u64),
+    /// Stopped after this many bytes, more than what was left of the quota
+    TooLarge(u64),
+}
+
+/// Fetches feeds and downloads episodes, clones share the same background refresh
+#[derive(Clone)]
+pub struct Podcasts {
+    client: reqwest::Client,
+    /// Bytes downloaded episodes may take
+    quota: u64,
+    refresh_interval: Duration,
+    /// Starts the next refresh early, after subscribing to something new
+    wake: Arc<Notify>,
+}
+
+impl Podcasts {
+    pub fn new(config: &PodcastConfig) -> Self {
+        Podcasts {
+            client: reqwest::Client::new(),
+            quota: config.quota_mb * 1024 * 1024,
+            refresh_interval: Duration::from_secs(config.refresh_minutes.max(1) * 60),
+            wake: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Refreshes right away and then every `refresh_minutes`, the shown folder reloads whenever
+    /// something changed
+    pub fn spawn(&self, state: State) {
+        let podcasts = self.clone();
+        tokio::spawn(async move {
+            loop {
+                if podcasts.refresh().await {
+                    state.dispatch(Action::LibraryChanged);
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(podcasts.refresh_interval) => {}
+                    _ = podcasts.wake.notified() => {}
+                }
+            }
+        });
+    }
+
+    /// Fetches the feed first, a typo in the URL is reported right away and not on some refresh
+    /// later. Returns the podcast's id.
+    pub async fn subscribe(&self, parent_id: i32, url: &str) -> Result<i32, LibraryError> {
+        let url = Url::parse(url.trim()).map_err(|error| LibraryError::Invalid(format!("{}: {}", url, error)))?;
+        let feed = self.fetch(url.as_str()).await?;
+        let id = mock::create_podcast(parent_id, url.as_str(), &feed)?;
+        self.wake.notify_one();
+        Ok(id)
+    }
+
+    /// Updates every podcast from its feed and downloads what fits. Returns whether the library
+    /// changed.
+    pub async fn refresh(&self) -> bool {
+        let mut changed = false;
+        for (id, url) in mock::podcast_feeds() {
+            match self.fetch(&url).await.and_then(|feed| mock::update_podcast(id, &feed)) {
+                Ok(updated) => changed |= updated,
+                // One broken feed must not keep the others from updating
+                Err(error) => eprintln!("Could not refresh podcast {}: {}", url, error),
+            }
+        }
+        self.download_episodes().await || changed
+    }
+
+    async fn fetch(&self, url: &str) -> Result<Feed, LibraryError> {
+        let response = self.client.get(url)
+            .timeout(FEED_TIMEOUT)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(network_error)?;
+        // Redirects count, relative enclosures are relative to where the feed ended up
+        let base = response.url().clone();
+        let xml = response.text().await.map_err(network_error)?;
+        parse_feed(&xml, &base)
+    }
+
+    /// Newest first, episodes that do not fit the quota are skipped and tried again on the next
+    /// refresh, something may have been played and deleted by then
+    async fn download_episodes(&self) -> bool {
+        let mut used = library_store::downloads_size();
+        let mut changed = false;
+        for episode in mock::pending_downloads() {
+            if used + episode.size() > self.quota {
+                continue;
+            }
+            match self.download(&episode, self.quota - used).await {
+                Ok(Download::Complete(name, size)) => match mock::episode_downloaded(episode.id, &name) {
+                    Ok(()) => {
+                        used += size;
+                        changed = true;
+                    }
+                    // Played or gone from the feed while it was downloading
+                    Err(_) => library_store::remove_download(&name),
+                },
+                Ok(Download::TooLarge(size)) => {
+                    if let Err(error) = mock::episode_too_large(episode.id, size) {
+                        eprintln!("Could not remember the size of episode {}: {}", episode.url, error);
+                    }
+                }
+                Err(error) => eprintln!("Could not download episode {}: {}", episode.url, error),
+            }
+        }
+        changed
+    }
+
+    /// Stops once more than `limit` bytes came in, the feed may have announced the wrong size or
+    /// none at all
+    async fn download(&self, episode: &StoredEpisode, limit: u64) -> Result<Download, LibraryError> {
+        let name = format!("{}.{}", episode.id, audio_extension(&episode.url));
+        let path = library_store::download_path(&name).ok_or_else(|| LibraryError::Network("no data directory".to_string()))?;
+        let partial_path = path.with_extension("part");
+        if let Some(directory) = path.parent() {
+            tokio::fs::create_dir_all(directory).await.map_err(LibraryError::from)?;
+        }
+
+        let mut response = self.client.get(&episode.url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(network_error)?;
+        let mut file = tokio::fs::File::create(&partial_path).await.map_err(LibraryError::from)?;
+        let mut size = 0;
+        let result = async {
+            while let Some(chunk) = response.chunk().await.map_err(network_error)? {
+                size += chunk.len() as u64;
+                if size > limit {
+                    return Ok(false);
+                }
+                file.write_all(&chunk).await.map_err(LibraryError::from)?;
+            }
+            file.flush().await.map_err(LibraryError::from).map(|()| true)
+        }.await;
+        match result {
+            Ok(true) => {}
+            fits => {
+                tokio::fs::remove_file(&partial_path).await.ok();
+                return fits.map(|_| Download::TooLarge(size));
+            }
+        }
+        tokio::fs::rename(&partial_path, &path).await.map_err(LibraryError::from)?;
+        Ok(Download::Complete(name, size))
+    }
+}
+
+/// RSS 2.0 and Atom. Items without audio are left out, a podcast is nothing but its episodes.
+fn parse_feed(xml: &str, base: &Url) -> Result<Feed, LibraryError> {
+    let not_a_feed = |reason: String| LibraryError::Invalid(format!("not a podcast feed: {}", reason));
+    let document = Document::parse(xml).map_err(|error| not_a_feed(error.to_string()))?;
+    let root = document.root_element();
+    let (channel, item_name) = match root.tag_name().name() {
+        "rss" => (child(root, "channel").ok_or_else(|| not_a_feed("no channel".to_string()))?, "item"),
+        "feed" => (root, "entry"),
+        other => return Err(not_a_feed(format!("<{}>", other))),
+    };
+
+    let episodes = channel.children()
+        .filter(|node| node.has_tag_name(item_name))
+        .filter_map(|item| if item_name == "item" { rss_episode(item, base) } else { atom_episode(item, base) })
+        .collect();
+    Ok(Feed {
+        title: child_text(channel, "title").unwrap_or_else(|| "Podcast".to_string()),
+        episodes,
+    })
+}
+
+fn rss_episode(item: Node, base: &Url) -> Option<FeedEpisode> {
+    let enclosure = child(item, "enclosure")?;
+    let url = base.join(enclosure.attribute("url")?).ok()?.to_string();
+    Some(FeedEpisode {
+        guid: child_text(item, "guid").unwrap_or_else(|| url.clone()),
+        title: child_text(item, "title").unwrap_or_else(|| url.clone()),
+        published: child_text(item, "pubDate")
+            .and_then(|date| DateTime::parse_from_rfc2822(&date).ok())
+            .map(|date| date.with_timezone(&Utc)),
+        length: enclosure.attribute("length").and_then(|length| length.parse().ok()).unwrap_or(0),
+        url,
+    })
+}
+
+fn atom_episode(entry: Node, base: &Url) -> Option<FeedEpisode> {
+    let enclosure = entry.children().find(|node| node.has_tag_name("link") && node.attribute("rel") == Some("enclosure"))?;
+    let url = base.join(enclosure.attribute("href")?).ok()?.to_string();
+    Some(FeedEpisode {
+        guid: child_text(entry, "id").unwrap_or_else(|| url.clone()),
+        title: child_text(entry, "title").unwrap_or_else(|| url.clone()),
+        published: child_text(entry, "published")
+            .or_else(|| child_text(entry, "updated"))
+            .and_then(|date| DateTime::parse_from_rfc3339(&date).ok())
+            .map(|date| date.with_timezone(&Utc)),
+        length: enclosure.attribute("length").and_then(|length| length.parse().ok()).unwrap_or(0),
+        url,
+    })
+}
+
+/// By local name, RSS feeds mix in iTunes and media namespaces with elements of their own
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.is_element() && child.tag_name().name() == name)
+}
+
+fn child_text(node: Node, name: &str) -> Option<String> {
+    let text: String = child(node, name)?.descendants().filter(|node| node.is_text()).filter_map(|node| node.text()).collect();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// The extension of the URL's path if it looks like one, players go by it
+fn audio_extension(url: &str) -> String {
+    Url::parse(url).ok()
+        .and_then(|url| {
+            let extension = url.path().rsplit_once('.')?.1.to_lowercase();
+            (!extension.is_empty() && extension.len() <= 5 && extension.chars().all(|c| c.is_ascii_alphanumeric())).then_some(extension)
+        })
+        .unwrap_or_else(|| "mp3".to_string())
+}
+
+fn network_error(error: reqwest::Error) -> LibraryError {
+    LibraryError::Network(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, sync::{atomic::{AtomicUsize, Ordering}, Arc}};
+
+    use axum::{extract::{Path, State as AxumState}, http::StatusCode, routing::get, Router};
+
+    use super::*;
+    use crate::{mock::ROOT_LIBRARY_ENTRY_ID, test_support};
+
+    /// Every refresh goes through every podcast, the tests must not count each other's downloads
+    static SERIAL: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+    fn fixtures() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures").join("podcasts")
+    }
+
+    /// Serves the fixtures on a free port of localhost, returns the address and a counter of the
+    /// episodes handed out
+    async fn serve_fixtures() -> (String, Arc<AtomicUsize>) {
+        let downloads = Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route("/{*path}", get(|AxumState(downloads): AxumState<Arc<AtomicUsize>>, Path(path): Path<String>| async move {
+                if path.starts_with("episodes/") {
+                    downloads.fetch_add(1, Ordering::Relaxed);
+                }
+                tokio::fs::read(fixtures().join(path)).await.map_err(|_| StatusCode::NOT_FOUND)
+            }))
+            .with_state(downloads.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (address, downloads)
+    }
+
+    fn episodes(podcast_id: i32) -> Vec<mock::LibraryEntry> {
+        mock::get_library_entry(podcast_id).unwrap().children.unwrap()
+    }
+
+    fn names(episodes: &[mock::LibraryEntry]) -> Vec<&str> {
+        episodes.iter().map(|episode| episode.name.as_str()).collect()
+    }
+
+    fn fixture_size(name: &str) -> u64 {
+        std::fs::metadata(fixtures().join("episodes").join(name)).unwrap().len()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn refreshes_fixture_feeds() {
+        let _serial = SERIAL.lock().await;
+        test_support::isolate_directories();
+        let (address, downloads) = serve_fixtures().await;
+        let podcasts = Podcasts::new(&PodcastConfig::default());
+
+        let rss = podcasts.subscribe(ROOT_LIBRARY_ENTRY_ID, &format!("http://{}/rss.xml", address)).await.unwrap();
+        let atom = podcasts.subscribe(ROOT_LIBRARY_ENTRY_ID, &format!("http://{}/atom.xml", address)).await.unwrap();
+        assert_eq!(mock::get_library_entry(rss).unwrap().name, "Gute-Nacht-Geschichten");
+        assert!(podcasts.subscribe(ROOT_LIBRARY_ENTRY_ID, &format!("http://{}/missing.xml", address)).await.is_err());
+        assert!(podcasts.refresh().await);
+
+        // Newest first, the item without audio is left out
+        let episodes_of_rss = episodes(rss);
+        assert_eq!(names(&episodes_of_rss), ["Der Mond schläft ein", "Die kleine Eule", "Der Igel im Laub"]);
+        let episodes_of_atom = episodes(atom);
+        assert_eq!(names(&episodes_of_atom), ["Wer klopft da am Baum?", "Spuren im Schnee"]);
+
+        for episode in episodes_of_rss.iter().chain(episodes_of_atom.iter()) {
+            assert_eq!(episode.variant, mock::Variant::Episode);
+            assert!(episode.unplayed);
+            let source = episode.track_source.as_ref().unwrap();
+            let url = source.url.as_deref().unwrap();
+            assert!(url.starts_with(&format!("http://{}/episodes/", address)), "{} is not resolved", url);
+            let file = url.rsplit('/').next().unwrap();
+            let local_path = source.local_path.as_ref().unwrap_or_else(|| panic!("{} was not downloaded", url));
+            assert_eq!(std::fs::metadata(local_path).unwrap().len(), fixture_size(file));
+        }
+        assert_eq!(downloads.load(Ordering::Relaxed), 5);
+
+        assert!(!podcasts.refresh().await);
+        assert_eq!(downloads.load(Ordering::Relaxed), 5);
+
+        mock::episode_played(episodes_of_rss[0].id).unwrap();
+        let played = &episodes(rss)[0];
+        assert!(!played.unplayed);
+        assert!(played.track_source.as_ref().unwrap().local_path.is_none());
+        assert!(!podcasts.refresh().await);
+        assert_eq!(downloads.load(Ordering::Relaxed), 5);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn remembers_episodes_that_outgrew_the_quota() {
+        let _serial = SERIAL.lock().await;
+        test_support::isolate_directories();
+        let (address, downloads) = serve_fixtures().await;
+        // The short episode fits, the long one does not and its feed does not say so
+        let quota = library_store::downloads_size() + fixture_size("gute-nacht-1.mp3") + 2_000;
+        let podcasts = Podcasts { quota, ..Podcasts::new(&PodcastConfig::default()) };
+
+        let id = podcasts.subscribe(ROOT_LIBRARY_ENTRY_ID, &format!("http://{}/unannounced.xml", address)).await.unwrap();
+        assert!(podcasts.refresh().await);
+        let [long, short] = &episodes(id)[..] else {
+            panic!("two episodes expected");
+        };
+        assert!(long.track_source.as_ref().unwrap().local_path.is_none());
+        assert!(short.track_source.as_ref().unwrap().local_path.is_some());
+        assert_eq!(downloads.load(Ordering::Relaxed), 2);
+
+        podcasts.refresh().await;
+        assert_eq!(downloads.load(Ordering::Relaxed), 2);
+    }
+}
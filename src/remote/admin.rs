@@ -0,0 +1,196 @@
+//! Library editing for the browser, every edit goes through the same library the device shows
+//! and reloads the shown folder right away
+//!
+//! Besides the token every route here wants the device's PIN, as an `X-Pin` header or `?pin=`.
+//! Five wrong ones in a row lock them for a minute.
+//!
+//! - `GET /admin` the page itself, it asks for the token and the PIN
+//! - `GET /api/tree` the whole library, whatever the active profile gets to see. The ids below are
+//!   the library's own, `0` is its root and not a profile's home.
+//! - `GET /api/tree/{id}/cover` the cover of any entry in the tree
+//! - `POST /api/library/{id}/children` a new folder, `{"name": ...}`
+//! - `POST /api/library/{id}/tracks?name=...&format=mp3` a new track, the audio as the body
+//! - `POST /api/library/{id}/podcasts` a podcast subscription, `{"url": ...}` of its RSS or Atom feed
+//! - `PATCH /api/library/{id}` rename or move, `{"name": ..., "parent_id": ...}`
+//! - `PUT /api/library/{id}/order` the children's ids in their new order
+//! - `DELETE /api/library/{id}` the entry with everything inside it
+//! - `PUT /api/library/{id}/cover` a new cover, the image as the body
+//! - `PUT /api/library/{id}/labels` genre, age rating and tags, `{"genre": ..., "age_rating": ..., "tags": [...]}`
+//! - `GET /api/profiles` the profiles and which one is active
+//! - `GET|PUT /api/limits?profile=...` a profile's parental limits, the active one's without a query
+//! - `GET|PUT /api/filter?profile=...` which entries a profile gets to see, likewise
+//! - `GET /api/unlock-gesture` the corners that unlock the child lock without the PIN, `0` is top
+//!   left and the others follow clockwise
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State as AxumState},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{cover_response, error_response, json, Remote};
+use crate::{library_store::EntryLabels, mock::{self, ContentFilter, LibraryError, ParentalLimits}, model::Action};
+
+/// Audio books run long, a few hundred megabytes have to fit through
+pub const TRACK_BODY_LIMIT: usize = 512 * 1024 * 1024;
+pub const COVER_BODY_LIMIT: usize = 16 * 1024 * 1024;
+
+pub async fn page() -> Html<&'static str> {
+    Html(include_str!("admin.html"))
+}
+
+#[derive(Deserialize)]
+pub struct NewFolder {
+    name: String,
+}
+
+#[derive(Deserialize)]
+pub struct NewTrack {
+    name: String,
+    /// File extension of the upload, like `mp3`
+    format: String,
+}
+
+#[derive(Deserialize)]
+pub struct NewPodcast {
+    url: String,
+}
+
+#[derive(Deserialize)]
+pub struct EntryChanges {
+    name: Option<String>,
+    parent_id: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct ProfileQuery {
+    profile: Option<String>,
+}
+
+impl ProfileQuery {
+    /// The named profile or the active one, `None` is the one used without profiles
+    fn resolve(self, remote: &Remote) -> Result<Option<String>, LibraryError> {
+        match self.profile {
+            Some(id) if remote.state.profiles().iter().any(|profile| profile.id == id) => Ok(Some(id)),
+            Some(id) => Err(LibraryError::Invalid(format!("unknown profile {}", id))),
+            None => Ok(remote.state.profile().map(|profile| profile.id)),
+        }
+    }
+}
+
+/// The device reloads what it shows, the answer carries whatever the edit returned
+fn edited<T: serde::Serialize>(remote: &Remote, result: Result<T, LibraryError>, status: StatusCode) -> Response {
+    match result {
+        Ok(value) => {
+            remote.state.dispatch(Action::LibraryChanged);
+            (status, Json(value)).into_response()
+        }
+        Err(error) => error_response(error),
+    }
+}
+
+pub async fn create_folder(AxumState(remote): AxumState<Remote>, Path(parent_id): Path<i32>, Json(folder): Json<NewFolder>) -> Response {
+    let result = mock::create_folder(parent_id, &folder.name).map(|id| json!({ "id": id }));
+    edited(&remote, result, StatusCode::CREATED)
+}
+
+pub async fn create_track(
+    AxumState(remote): AxumState<Remote>,
+    Path(parent_id): Path<i32>,
+    Query(track): Query<NewTrack>,
+    audio: Bytes,
+) -> Response {
+    let result = mock::create_track(parent_id, &track.name, &audio, &track.format).map(|id| json!({ "id": id }));
+    edited(&remote, result, StatusCode::CREATED)
+}
+
+/// Answers once the feed was fetched, the episodes download in the background
+pub async fn subscribe_podcast(AxumState(remote): AxumState<Remote>, Path(parent_id): Path<i32>, Json(podcast): Json<NewPodcast>) -> Response {
+    let result = remote.podcasts.subscribe(parent_id, &podcast.url).await.map(|id| json!({ "id": id }));
+    edited(&remote, result, StatusCode::CREATED)
+}
+
+/// Renames first, a failed move leaves the new name in place
+pub async fn update_entry(AxumState(remote): AxumState<Remote>, Path(id): Path<i32>, Json(changes): Json<EntryChanges>) -> Response {
+    let mut result = Ok(());
+    if let Some(name) = changes.name {
+        result = mock::rename_entry(id, &name);
+    }
+    if let (Ok(()), Some(parent_id)) = (&result, changes.parent_id) {
+        result = mock::move_entry(id, parent_id);
+    }
+    edited(&remote, result, StatusCode::OK)
+}
+
+pub async fn reorder_children(AxumState(remote): AxumState<Remote>, Path(parent_id): Path<i32>, Json(ids): Json<Vec<i32>>) -> Response {
+    edited(&remote, mock::reorder_children(parent_id, &ids), StatusCode::OK)
+}
+
+pub async fn delete_entry(AxumState(remote): AxumState<Remote>, Path(id): Path<i32>) -> Response {
+    edited(&remote, mock::delete_entry(id), StatusCode::OK)
+}
+
+pub async fn get_tree() -> Response {
+    match mock::library_tree() {
+        Ok(root) => Json(json::entry(&root)).into_response(),
+        Err(error) => error_response(error),
+    }
+}
+
+pub async fn get_tree_cover(Path(id): Path<i32>) -> Response {
+    cover_response(mock::any_cover(id))
+}
+
+pub async fn set_cover(AxumState(remote): AxumState<Remote>, Path(id): Path<i32>, image: Bytes) -> Response {
+    edited(&remote, mock::set_cover(id, image.to_vec()), StatusCode::OK)
+}
+
+pub async fn set_labels(AxumState(remote): AxumState<Remote>, Path(id): Path<i32>, Json(labels): Json<EntryLabels>) -> Response {
+    edited(&remote, mock::set_labels(id, labels), StatusCode::OK)
+}
+
+pub async fn get_profiles(AxumState(remote): AxumState<Remote>) -> Response {
+    let active = remote.state.profile().map(|profile| profile.id);
+    Json(json!({ "profiles": remote.state.profiles(), "active": active })).into_response()
+}
+
+pub async fn get_unlock_gesture(AxumState(remote): AxumState<Remote>) -> Response {
+    Json(json!({ "corners": remote.state.unlock_gesture() })).into_response()
+}
+
+pub async fn get_limits(AxumState(remote): AxumState<Remote>, Query(query): Query<ProfileQuery>) -> Response {
+    match query.resolve(&remote) {
+        Ok(profile) => Json(mock::profile_limits(profile.as_deref())).into_response(),
+        Err(error) => error_response(error),
+    }
+}
+
+pub async fn set_limits(
+    AxumState(remote): AxumState<Remote>,
+    Query(query): Query<ProfileQuery>,
+    Json(limits): Json<ParentalLimits>,
+) -> Response {
+    let result = query.resolve(&remote).and_then(|profile| mock::set_parental_limits(profile.as_deref(), limits));
+    edited(&remote, result, StatusCode::OK)
+}
+
+pub async fn get_filter(AxumState(remote): AxumState<Remote>, Query(query): Query<ProfileQuery>) -> Response {
+    match query.resolve(&remote) {
+        Ok(profile) => Json(mock::content_filter(profile.as_deref())).into_response(),
+        Err(error) => error_response(error),
+    }
+}
+
+/// The device drops what the filter now hides from the shown folder, the queue and search
+pub async fn set_filter(
+    AxumState(remote): AxumState<Remote>,
+    Query(query): Query<ProfileQuery>,
+    Json(filter): Json<ContentFilter>,
+) -> Response {
+    let result = query.resolve(&remote).and_then(|profile| mock::set_content_filter(profile.as_deref(), filter));
+    edited(&remote, result, StatusCode::OK)
+}
@@ -0,0 +1,79 @@
+use serde_json::{json, Value};
+
+use crate::{mock::LibraryEntry, model::{Field, State}};
+
+/// Everything but the cover bytes, those are served separately
+pub fn entry(entry: &LibraryEntry) -> Value {
+    json!({
+        "id": entry.id,
+        "parent_id": entry.parent_id,
+        "variant": format!("{:?}", entry.variant),
+        "name": entry.name,
+        "has_image": entry.image.is_some(),
+        "played_at": entry.played_at,
+        "added_at": entry.added_at,
+        "sort_key": entry.sort_key,
+        "favourite": entry.favourite,
+        "pinned": entry.pinned,
+        "unplayed": entry.unplayed,
+        "genre": entry.genre,
+        "age_rating": entry.age_rating,
+        "tags": entry.tags,
+        "children": entry.children.as_ref().map(|children| children.iter().map(self::entry).collect::<Vec<_>>()),
+        "ancestors": entry.ancestors.as_ref().map(|ancestors| {
+            ancestors.iter().map(|crumb| json!({ "id": crumb.id, "name": crumb.name })).collect::<Vec<_>>()
+        }),
+    })
+}
+
+/// Name and value of a change, the name matches the `Field` variant
+pub fn field(field: &Field) -> (&'static str, Value) {
+    match field {
+        Field::messages(messages) => ("messages", messages.iter().map(|message| json!({
+            "id": message.id,
+            "severity": format!("{:?}", message.severity),
+            "text": message.text,
+            "count": message.count,
+        })).collect()),
+        Field::active_library_entry(active) => ("active_library_entry", active.as_ref().map(entry).into()),
+        Field::navigation(navigation) => ("navigation", json!({
+            "current": navigation.current.map(|location| location.id),
+            "can_go_back": !navigation.back.is_empty(),
+            "can_go_forward": navigation.can_go_forward(),
+        })),
+        Field::playing_library_entry(playing) => ("playing_library_entry", playing.as_ref().map(entry).into()),
+        Field::is_playing(is_playing) => ("is_playing", json!(is_playing)),
+        Field::progress(progress) => ("progress", json!({
+            "position": progress.position().as_secs_f64(),
+            "duration": progress.duration().as_secs_f64(),
+        })),
+        Field::queue(queue) => ("queue", queue.iter().map(entry).collect()),
+        Field::resume_positions(positions) => ("resume_positions", positions.iter()
+            .map(|(id, progress)| (id.to_string(), json!(progress.position().as_secs_f64())))
+            .collect::<serde_json::Map<_, _>>()
+            .into()),
+        Field::search_query(query) => ("search_query", json!(query)),
+        Field::search_results(results) => ("search_results", results.iter().map(entry).collect()),
+        Field::edit_mode(edit_mode) => ("edit_mode", json!(edit_mode)),
+        Field::undo_stack(steps) => ("undo_stack", steps.iter().map(|step| json!(step.label)).collect()),
+        Field::volume(volume) => ("volume", json!(volume)),
+        Field::locked(locked) => ("locked", json!(locked)),
+        Field::time_is_up(time_is_up) => ("time_is_up", json!(time_is_up)),
+        Field::profile(profile) => ("profile", json!(profile)),
+    }
+}
+
+/// Changes as one object, a field changed twice keeps its last value
+pub fn changes(fields: &[Field]) -> Value {
+    let mut changes = serde_json::Map::new();
+    for change in fields {
+        let (name, value) = field(change);
+        changes.insert(name.to_string(), value);
+    }
+    Value::Object(changes)
+}
+
+/// The whole state in the same shape as the changes, clients start from this
+pub fn snapshot(state: &State) -> Value {
+    changes(&state.fields())
+}
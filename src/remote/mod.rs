@@ -0,0 +1,340 @@
+//! Remote control over HTTP for phones on the home network, only started when configured
+//!
+//! - `GET /api/state` the whole state, shaped like the change events
+//! - `GET /api/library/{id}` an entry with its children
+//! - `GET /api/library/{id}/cover` its cover, the image as it was uploaded
+//! - `POST /api/actions` a [`RemoteAction`] as JSON
+//! - `GET /api/events` WebSocket, a snapshot first and then every change as it happens
+//!
+//! Editing the library from the browser lives in [`admin`], those routes want the device's PIN as
+//! well.
+
+mod admin;
+mod json;
+#[cfg(test)]
+mod tests;
+
+use std::{sync::{Arc, Mutex}, time::{Duration, Instant}};
+
+use axum::{
+    extract::{ws::{Message, WebSocket, WebSocketUpgrade}, DefaultBodyLimit, Path, Query, Request, State as AxumState},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, patch, post, put},
+    Json, Router,
+};
+use serde::Deserialize;
+use tokio::sync::broadcast;
+
+use crate::{config::RemoteConfig, mock::{self, get_library_entry, ErrorKind, LibraryError}, model::{actions::Unlock, Action, State}, podcasts::Podcasts};
+
+/// What the remote may ask for, a subset of `Action` with ids instead of whole entries
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RemoteAction {
+    Open { id: i32 },
+    Back,
+    Forward,
+    Home,
+    /// Like a tap: folders open, tracks play, the loaded track pauses or resumes
+    Select { id: i32 },
+    Play { id: i32 },
+    Resume { id: i32 },
+    Restart { id: i32 },
+    PlayNext { id: i32 },
+    AddToQueue { id: i32 },
+    TogglePlay,
+    Pause,
+    Seek { seconds: f64 },
+    ToggleFavourite { id: i32 },
+    TogglePinned { id: i32 },
+    Search { query: String },
+    DismissMessage { id: u64 },
+    /// Between 0 and 1
+    SetVolume { volume: f32 },
+    Lock,
+    Unlock { pin: String },
+    GrantExtraMinutes { pin: String, minutes: u32 },
+    SwitchProfile { id: String },
+}
+
+impl RemoteAction {
+    fn into_action(self, state: &State) -> Result<Option<Action>, LibraryError> {
+        let action = match self {
+            RemoteAction::Open { id } => Action::LoadLibraryEntry(id),
+            RemoteAction::Back => Action::GoBack,
+            RemoteAction::Forward => Action::GoForward,
+            RemoteAction::Home => Action::GoHome,
+            RemoteAction::Select { id } => Action::SelectLibraryEntry(id),
+            RemoteAction::Play { id } => Action::PlayLibraryEntry(get_library_entry(id)?),
+            RemoteAction::Resume { id } => Action::ResumeLibraryEntry(id),
+            RemoteAction::Restart { id } => Action::RestartLibraryEntry(id),
+            RemoteAction::PlayNext { id } => Action::PlayNext(id),
+            RemoteAction::AddToQueue { id } => Action::AddToQueue(id),
+            RemoteAction::TogglePlay => Action::TogglePlay(state.is_playing()),
+            // Pausing twice must not resume
+            RemoteAction::Pause if !state.is_playing() => return Ok(None),
+            RemoteAction::Pause => Action::TogglePlay(true),
+            RemoteAction::Seek { seconds } => Action::Seek(Duration::try_from_secs_f64(seconds)
+                .map_err(|_| LibraryError::Invalid(format!("cannot seek to {} seconds", seconds)))?),
+            RemoteAction::ToggleFavourite { id } => Action::ToggleFavourite(id),
+            RemoteAction::TogglePinned { id } => Action::TogglePinned(id),
+            RemoteAction::Search { query } => Action::Search(query),
+            RemoteAction::DismissMessage { id } => Action::DismissMessage(id),
+            RemoteAction::SetVolume { volume } => Action::SetVolume(volume),
+            RemoteAction::Lock => Action::Lock,
+            RemoteAction::Unlock { pin } => Action::Unlock(Unlock::Pin(pin)),
+            RemoteAction::GrantExtraMinutes { pin, minutes } => Action::GrantExtraMinutes(pin, minutes),
+            RemoteAction::SwitchProfile { id } => Action::SwitchProfile(id),
+        };
+        Ok(Some(action))
+    }
+}
+
+#[derive(Clone)]
+struct Remote {
+    state: State,
+    podcasts: Podcasts,
+    token: String,
+    /// Every change batch as JSON, each WebSocket subscribes on its own
+    events: broadcast::Sender<String>,
+    wrong_pins: Arc<Mutex<WrongPins>>,
+}
+
+/// Guessing the PIN of the admin routes takes a while
+#[derive(Default)]
+struct WrongPins {
+    count: u32,
+    locked_until: Option<Instant>,
+}
+
+impl Remote {
+    fn new(state: State, podcasts: Podcasts, token: String) -> Self {
+        let (events, _) = broadcast::channel(64);
+        {
+            let events = events.clone();
+            state.subscribe(move |changes| {
+                // Nobody listening is not an error
+                events.send(json::changes(&changes).to_string()).ok();
+            });
+        }
+        Remote { state, podcasts, token, events, wrong_pins: Arc::default() }
+    }
+
+    /// Wrong PINs count towards the lockout, the right one starts the count over
+    fn check_pin(&self, pin: &str, expected: &str) -> Result<(), StatusCode> {
+        let mut wrong_pins = self.wrong_pins.lock().unwrap();
+        if wrong_pins.locked_until.is_some_and(|until| Instant::now() < until) {
+            return Err(StatusCode::TOO_MANY_REQUESTS);
+        }
+        if tokens_match(pin, expected) {
+            *wrong_pins = WrongPins::default();
+            return Ok(());
+        }
+        wrong_pins.count += 1;
+        if wrong_pins.count >= MAX_WRONG_PINS {
+            *wrong_pins = WrongPins { count: 0, locked_until: Some(Instant::now() + PIN_LOCKOUT) };
+        }
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+/// Binds and serves in the background, a port that is taken is reported but not fatal
+pub fn serve(state: State, podcasts: Podcasts, config: RemoteConfig) {
+    if config.token.is_empty() {
+        eprintln!("Remote control disabled, it needs a token");
+        return;
+    }
+
+    let app = router(Remote::new(state, podcasts, config.token));
+    tokio::spawn(async move {
+        let listener = match tokio::net::TcpListener::bind(config.listen).await {
+            Ok(listener) => listener,
+            Err(error) => {
+                eprintln!("Remote control could not listen on {}: {}", config.listen, error);
+                return;
+            }
+        };
+        println!("Remote control listening on {}", config.listen);
+        if let Err(error) = axum::serve(listener, app).await {
+            eprintln!("Remote control stopped: {}", error);
+        }
+    });
+}
+
+fn router(remote: Remote) -> Router {
+    let admin = Router::new()
+        .route("/api/tree", get(admin::get_tree))
+        .route("/api/tree/{id}/cover", get(admin::get_tree_cover))
+        .route("/api/library/{id}", patch(admin::update_entry).delete(admin::delete_entry))
+        .route("/api/library/{id}/children", post(admin::create_folder))
+        .route("/api/library/{id}/podcasts", post(admin::subscribe_podcast))
+        .route("/api/library/{id}/tracks", post(admin::create_track).layer(DefaultBodyLimit::max(admin::TRACK_BODY_LIMIT)))
+        .route("/api/library/{id}/order", put(admin::reorder_children))
+        .route("/api/library/{id}/labels", put(admin::set_labels))
+        .route("/api/library/{id}/cover", put(admin::set_cover).layer(DefaultBodyLimit::max(admin::COVER_BODY_LIMIT)))
+        .route("/api/profiles", get(admin::get_profiles))
+        .route("/api/limits", get(admin::get_limits).put(admin::set_limits))
+        .route("/api/filter", get(admin::get_filter).put(admin::set_filter))
+        .route("/api/unlock-gesture", get(admin::get_unlock_gesture))
+        .route_layer(middleware::from_fn_with_state(remote.clone(), authorize_admin));
+
+    let api = Router::new()
+        .route("/api/state", get(get_state))
+        .route("/api/library/{id}", get(get_library))
+        .route("/api/library/{id}/cover", get(get_cover))
+        .route("/api/actions", post(post_action))
+        .route("/api/events", get(events))
+        .merge(admin)
+        .layer(middleware::from_fn_with_state(remote.clone(), authorize));
+
+    // The page holds no data, it asks for the token and the PIN before it loads any
+    Router::new()
+        .route("/admin", get(admin::page))
+        .merge(api)
+        .with_state(remote)
+}
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+async fn authorize(AxumState(remote): AxumState<Remote>, request: Request, next: Next) -> Response {
+    let header = request.headers()
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string);
+    // Percent-decoded, the admin page encodes the token for cover URLs
+    let query = Query::<TokenQuery>::try_from_uri(request.uri()).ok().and_then(|Query(query)| query.token);
+
+    if header.or(query).is_some_and(|token| tokens_match(&token, &remote.token)) {
+        next.run(request).await
+    } else {
+        StatusCode::UNAUTHORIZED.into_response()
+    }
+}
+
+#[derive(Deserialize)]
+struct PinQuery {
+    pin: Option<String>,
+}
+
+/// Header the admin routes take the PIN from, or `?pin=` where the browser cannot set one
+const PIN_HEADER: &str = "x-pin";
+/// Wrong PINs in a row before the admin routes lock
+const MAX_WRONG_PINS: u32 = 5;
+const PIN_LOCKOUT: Duration = Duration::from_secs(60);
+
+/// The token lets phones control playback, editing the library and the limits takes the PIN the
+/// parents use on the device as well. Without one set up the admin routes stay closed.
+async fn authorize_admin(AxumState(remote): AxumState<Remote>, request: Request, next: Next) -> Response {
+    let Some(expected) = remote.state.pin() else {
+        return (StatusCode::FORBIDDEN, "no PIN set up").into_response();
+    };
+    let header = request.headers()
+        .get(PIN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let query = Query::<PinQuery>::try_from_uri(request.uri()).ok().and_then(|Query(query)| query.pin);
+    let Some(pin) = header.or(query).filter(|pin| !pin.is_empty()) else {
+        return StatusCode::FORBIDDEN.into_response();
+    };
+
+    if let Err(status) = remote.check_pin(&pin, &expected) {
+        return status.into_response();
+    }
+    next.run(request).await
+}
+
+/// Compares every byte, how long it takes tells nothing about how close a guess was
+fn tokens_match(given: &str, expected: &str) -> bool {
+    let (given, expected) = (given.as_bytes(), expected.as_bytes());
+    given.len() == expected.len() && given.iter().zip(expected).fold(0, |difference, (a, b)| difference | (a ^ b)) == 0
+}
+
+fn error_response(error: LibraryError) -> Response {
+    let status = match error.kind() {
+        ErrorKind::NotFound => StatusCode::NOT_FOUND,
+        ErrorKind::Busy => StatusCode::SERVICE_UNAVAILABLE,
+        // Only uploads are decoded here, a broken one is the client's fault
+        ErrorKind::Decode | ErrorKind::Invalid => StatusCode::UNPROCESSABLE_ENTITY,
+        ErrorKind::Network => StatusCode::BAD_GATEWAY,
+    };
+    (status, error.to_string()).into_response()
+}
+
+async fn get_state(AxumState(remote): AxumState<Remote>) -> Response {
+    Json(json::snapshot(&remote.state)).into_response()
+}
+
+async fn get_library(AxumState(_): AxumState<Remote>, Path(id): Path<i32>) -> Response {
+    match get_library_entry(id) {
+        Ok(entry) => Json(json::entry(&entry)).into_response(),
+        Err(error) => error_response(error),
+    }
+}
+
+/// Only what the active profile may see, the parents find every cover in the tree
+async fn get_cover(Path(id): Path<i32>) -> Response {
+    cover_response(mock::cover(id))
+}
+
+fn cover_response(cover: Result<Option<Vec<u8>>, LibraryError>) -> Response {
+    match cover {
+        Ok(Some(image)) => {
+            let content_type = image::guess_format(&image)
+                .map(|format| format.to_mime_type())
+                .unwrap_or("application/octet-stream");
+            ([(header::CONTENT_TYPE, content_type)], image).into_response()
+        }
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(error) => error_response(error),
+    }
+}
+
+/// Accepted means queued, whatever the action leads to shows up on the event stream
+async fn post_action(AxumState(remote): AxumState<Remote>, Json(action): Json<RemoteAction>) -> Response {
+    match action.into_action(&remote.state) {
+        Ok(Some(action)) => {
+            remote.state.dispatch(action);
+            StatusCode::ACCEPTED.into_response()
+        }
+        Ok(None) => StatusCode::NO_CONTENT.into_response(),
+        Err(error) => error_response(error),
+    }
+}
+
+async fn events(AxumState(remote): AxumState<Remote>, upgrade: WebSocketUpgrade) -> Response {
+    upgrade.on_upgrade(move |socket| stream_events(socket, remote))
+}
+
+async fn stream_events(mut socket: WebSocket, remote: Remote) {
+    let mut events = remote.events.subscribe();
+    if socket.send(Message::Text(json::snapshot(&remote.state).to_string().into())).await.is_err() {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let text = match event {
+                    Ok(text) => text,
+                    // Too slow to keep up, starting over from the current state is simpler than catching up
+                    Err(broadcast::error::RecvError::Lagged(_)) => json::snapshot(&remote.state).to_string(),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if socket.send(Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                // The stream is one way, actions go through the HTTP endpoint
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+use super::{router, Remote};
+use crate::{config::PodcastConfig, podcasts::Podcasts, test_support::{self, PIN}};
+
+/// Has the characters a query string mangles when they are not encoded
+const TOKEN: &str = "a+b/c=d%e";
+const ENCODED_TOKEN: &str = "a%2Bb%2Fc%3Dd%25e";
+
+/// Serves on a free port of localhost, returns the address
+async fn serve() -> String {
+    let remote = Remote::new(test_support::state(), Podcasts::new(&PodcastConfig::default()), TOKEN.to_string());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router(remote)).await.unwrap() });
+    address.to_string()
+}
+
+type Socket = tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+async fn next_event(socket: &mut Socket) -> Value {
+    match tokio::time::timeout(Duration::from_secs(5), socket.next()).await {
+        Ok(Some(Ok(WsMessage::Text(text)))) => serde_json::from_str(&text).unwrap(),
+        other => panic!("no event: {:?}", other),
+    }
+}
+
+async fn send(request: reqwest::RequestBuilder) -> (u16, Value) {
+    let response = request.send().await.unwrap();
+    let status = response.status().as_u16();
+    let text = response.text().await.unwrap();
+    (status, serde_json::from_str(&text).unwrap_or(Value::Null))
+}
+
+fn post_json(address: &str, path: &str, body: Value) -> reqwest::RequestBuilder {
+    reqwest::Client::new()
+        .post(format!("http://{}{}", address, path))
+        .bearer_auth(TOKEN)
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn requires_the_token() {
+    let address = serve().await;
+    let client = reqwest::Client::new();
+    let state = format!("http://{}/api/state", address);
+
+    assert_eq!(send(client.get(&state)).await.0, 401);
+    assert_eq!(send(client.get(&state).bearer_auth("wrong")).await.0, 401);
+    assert_eq!(send(client.get(&state).bearer_auth(TOKEN)).await.0, 200);
+    assert_eq!(send(client.get(format!("{}?token={}", state, ENCODED_TOKEN))).await.0, 200);
+    // Unencoded the `+` is a space
+    assert_eq!(send(client.get(format!("{}?token={}", state, TOKEN))).await.0, 401);
+    // The page itself holds no data
+    assert_eq!(send(client.get(format!("http://{}/admin", address))).await.0, 200);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn admin_routes_require_the_pin() {
+    let address = serve().await;
+    let client = reqwest::Client::new();
+    let limits = || client.get(format!("http://{}/api/limits", address)).bearer_auth(TOKEN);
+
+    assert_eq!(send(limits()).await.0, 403);
+    assert_eq!(send(limits().header("X-Pin", PIN)).await.0, 200);
+    assert_eq!(send(client.get(format!("http://{}/api/limits?pin={}", address, PIN)).bearer_auth(TOKEN)).await.0, 200);
+    // The token alone still controls playback
+    assert_eq!(send(post_json(&address, "/api/actions", json!({ "type": "home" }))).await.0, 202);
+
+    for _ in 0..5 {
+        assert_eq!(send(limits().header("X-Pin", "0000")).await.0, 403);
+    }
+    assert_eq!(send(limits().header("X-Pin", PIN)).await.0, 429);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn only_parents_get_the_unlock_gesture() {
+    let address = serve().await;
+    let client = reqwest::Client::new();
+    let gesture = || client.get(format!("http://{}/api/unlock-gesture", address)).bearer_auth(TOKEN);
+
+    assert_eq!(send(gesture()).await.0, 403);
+    let (status, body) = send(gesture().header("X-Pin", PIN)).await;
+    assert_eq!(status, 200);
+    let corners = body["corners"].as_array().unwrap();
+    assert_eq!(corners.len(), 6);
+    assert!(corners.iter().all(|corner| corner.as_u64().unwrap() < 4));
+    assert!(corners.windows(2).all(|pair| pair[0] != pair[1]));
+
+    let (_, state) = send(client.get(format!("http://{}/api/state", address)).bearer_auth(TOKEN)).await;
+    assert!(!state.to_string().contains("gesture"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn state_has_every_field() {
+    let address = serve().await;
+    let (status, state) = send(reqwest::Client::new().get(format!("http://{}/api/state", address)).bearer_auth(TOKEN)).await;
+
+    assert_eq!(status, 200);
+    for field in ["messages", "active_library_entry", "queue", "edit_mode", "volume", "locked", "time_is_up", "profile"] {
+        assert!(state.get(field).is_some(), "{} missing from {}", field, state);
+    }
+    assert_eq!(state["locked"], json!(false));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn streams_the_changes_of_actions() {
+    let address = serve().await;
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/api/events?token={}", address, ENCODED_TOKEN)).await.unwrap();
+    let snapshot = next_event(&mut socket).await;
+    assert!(snapshot.get("search_query").is_some());
+
+    let (status, _) = send(post_json(&address, "/api/actions", json!({ "type": "search", "query": "Boomschakalaka" }))).await;
+    assert_eq!(status, 202);
+    loop {
+        let changes = next_event(&mut socket).await;
+        if changes["search_query"] == json!("Boomschakalaka") {
+            break;
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn creates_folders() {
+    let address = serve().await;
+    let (status, created) = send(post_json(&address, "/api/library/0/children", json!({ "name": "Aus dem Test" })).header("X-Pin", PIN)).await;
+    assert_eq!(status, 201);
+
+    let id = created["id"].as_i64().unwrap();
+    let (status, folder) = send(reqwest::Client::new().get(format!("http://{}/api/library/{}", address, id)).bearer_auth(TOKEN)).await;
+    assert_eq!(status, 200);
+    assert_eq!(folder["name"], json!("Aus dem Test"));
+    assert_eq!(folder["variant"], json!("folder"));
+
+    let (status, _) = send(post_json(&address, "/api/library/0/children", json!({ "name": "  " })).header("X-Pin", PIN)).await;
+    assert_eq!(status, 422);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tree_shows_what_the_filter_hides() {
+    let address = serve().await;
+    let client = reqwest::Client::new();
+    let filter = |blocked_tags: Value| client.put(format!("http://{}/api/filter", address))
+        .bearer_auth(TOKEN)
+        .header("X-Pin", PIN)
+        .header("Content-Type", "application/json")
+        .body(json!({ "max_age_rating": null, "hide_unrated": false, "blocked_tags": blocked_tags }).to_string());
+
+    // Kitahits is tagged as such
+    assert_eq!(send(filter(json!(["kinderlieder"]))).await.0, 200);
+    let (status, _) = send(client.get(format!("http://{}/api/library/6", address)).bearer_auth(TOKEN)).await;
+    assert_eq!(status, 404);
+
+    let (status, tree) = send(client.get(format!("http://{}/api/tree", address)).bearer_auth(TOKEN).header("X-Pin", PIN)).await;
+    assert_eq!(status, 200);
+    let music = tree["children"].as_array().unwrap().iter().find(|child| child["id"] == json!(3)).unwrap();
+    assert!(music["children"].as_array().unwrap().iter().any(|child| child["name"] == json!("Kitahits")));
+    let (status, _) = send(client.get(format!("http://{}/api/tree/6/cover?pin={}", address, PIN)).bearer_auth(TOKEN)).await;
+    assert_eq!(status, 200);
+    // Phones only get to see what the kids do
+    let (status, _) = send(client.get(format!("http://{}/api/library/6/cover", address)).bearer_auth(TOKEN)).await;
+    assert_eq!(status, 404);
+    let (status, _) = send(client.get(format!("http://{}/api/tree", address)).bearer_auth(TOKEN)).await;
+    assert_eq!(status, 403);
+
+    assert_eq!(send(filter(json!([]))).await.0, 200);
+    let (status, _) = send(client.get(format!("http://{}/api/library/6/cover", address)).bearer_auth(TOKEN)).await;
+    assert_eq!(status, 200);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn refuses_covers_that_do_not_decode() {
+    let address = serve().await;
+    let mut broken = b"\x89PNG\r\n\x1a\n".to_vec();
+    broken.extend_from_slice(&[0; 32]);
+
+    let cover = reqwest::Client::new().put(format!("http://{}/api/library/3/cover", address)).bearer_auth(TOKEN).header("X-Pin", PIN);
+    let (status, _) = send(cover.body(broken)).await;
+    assert_eq!(status, 422);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn refuses_positions_no_track_has() {
+    let address = serve().await;
+    for seconds in [-1.0, 1e300] {
+        let (status, _) = send(post_json(&address, "/api/actions", json!({ "type": "seek", "seconds": seconds }))).await;
+        assert_eq!(status, 422, "seeking to {}", seconds);
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::mock::{LibraryEntry, ROOT_LIBRARY_ENTRY_ID};
+
+/// How much a match counts depending on where the word was found
+const NAME_WEIGHT: u32 = 3;
+const TAG_WEIGHT: u32 = 2;
+const PARENT_WEIGHT: u32 = 1;
+
+/// Lower case with umlauts spelled out and other accents dropped, so "Hörspiel", "hoerspiel" and
+/// "HOERSPIEL" all end up the same
+pub fn normalize(text: &str) -> String {
+    let mut normalized = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase) {
+        match c {
+            'ä' => normalized.push_str("ae"),
+            'ö' => normalized.push_str("oe"),
+            'ü' => normalized.push_str("ue"),
+            'ß' => normalized.push_str("ss"),
+            'à' | 'á' | 'â' | 'ã' | 'å' => normalized.push('a'),
+            'ç' => normalized.push('c'),
+            'è' | 'é' | 'ê' | 'ë' => normalized.push('e'),
+            'ì' | 'í' | 'î' | 'ï' => normalized.push('i'),
+            'ñ' => normalized.push('n'),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ø' => normalized.push('o'),
+            'ù' | 'ú' | 'û' => normalized.push('u'),
+            'ý' | 'ÿ' => normalized.push('y'),
+            c => normalized.push(c),
+        }
+    }
+    normalized
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(normalize)
+}
+
+/// Inverted index from normalized words to the entries containing them
+#[derive(Default)]
+pub struct SearchIndex {
+    words: BTreeMap<String, HashMap<i32, u32>>,
+}
+
+impl SearchIndex {
+    /// `entries` have to be the whole library, parent names are looked up among them
+    pub fn build(entries: &[LibraryEntry]) -> Self {
+        let names: HashMap<i32, (&str, Option<i32>)> = entries.iter()
+            .map(|entry| (entry.id, (entry.name.as_str(), entry.parent_id)))
+            .collect();
+
+        let mut index = SearchIndex::default();
+        for entry in entries {
+            for word in words(&entry.name) {
+                index.add(word, entry.id, NAME_WEIGHT);
+            }
+            for tag in entry.genre.iter().chain(entry.tags.iter()) {
+                for word in words(tag) {
+                    index.add(word, entry.id, TAG_WEIGHT);
+                }
+            }
+
+            // Every folder above counts, "Folge 3" is found when searching for the series
+            let mut parent_id = entry.parent_id;
+            let mut depth = 0;
+            // The root is everybody's parent, its name would match everything
+            while let Some((name, next)) = parent_id.filter(|id| *id != ROOT_LIBRARY_ENTRY_ID).and_then(|id| names.get(&id)) {
+                for word in words(name) {
+                    index.add(word, entry.id, PARENT_WEIGHT);
+                }
+                parent_id = *next;
+                // Guard against broken data pointing in circles
+                depth += 1;
+                if depth > entries.len() {
+                    break;
+                }
+            }
+        }
+        index
+    }
+
+    fn add(&mut self, word: String, id: i32, weight: u32) {
+        let weights = self.words.entry(word).or_default();
+        let current = weights.entry(id).or_default();
+        *current = (*current).max(weight);
+    }
+
+    /// Ids of entries where every word of the query starts one of their words, best matches first
+    /// and equally good ones by id, so the same query always gives the same list
+    pub fn search(&self, query: &str) -> Vec<i32> {
+        let mut scores: Option<HashMap<i32, u32>> = None;
+        for query_word in words(query) {
+            // Best weight per entry among all words starting with the query word
+            let mut matches: HashMap<i32, u32> = HashMap::new();
+            for (_, weights) in self.words.range(query_word.clone()..).take_while(|(word, _)| word.starts_with(&query_word)) {
+                for (id, weight) in weights {
+                    let best = matches.entry(*id).or_default();
+                    *best = (*best).max(*weight);
+                }
+            }
+
+            scores = Some(match scores {
+                None => matches,
+                Some(scores) => scores.into_iter()
+                    .filter_map(|(id, score)| matches.get(&id).map(|weight| (id, score + weight)))
+                    .collect(),
+            });
+        }
+
+        let mut results: Vec<(i32, u32)> = scores.unwrap_or_default().into_iter().collect();
+        results.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        results.into_iter().map(|(id, _)| id).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folder(id: i32, name: &str, parent_id: i32) -> LibraryEntry {
+        LibraryEntry::new_folder(id, name, Some(parent_id), "".to_string(), id)
+    }
+
+    fn track(id: i32, name: &str, parent_id: i32) -> LibraryEntry {
+        LibraryEntry::new_spotify(id, name, Some(parent_id), id)
+    }
+
+    #[test]
+    fn spells_out_umlauts() {
+        assert_eq!(normalize("Hörspiel"), "hoerspiel");
+        assert_eq!(normalize("ÄRGER"), "aerger");
+        assert_eq!(normalize("Tür"), "tuer");
+        assert_eq!(normalize("Straße"), "strasse");
+        assert_eq!(normalize("Crème Brûlée"), "creme brulee");
+    }
+
+    #[test]
+    fn finds_words_by_their_start() {
+        let index = SearchIndex::build(&[
+            folder(1, "Bibi Blocksberg", ROOT_LIBRARY_ENTRY_ID),
+            track(2, "Der Hexengeburtstag", 1),
+            track(3, "Bobo Siebenschläfer", ROOT_LIBRARY_ENTRY_ID),
+        ]);
+
+        assert_eq!(index.search("hex"), vec![2]);
+        assert_eq!(index.search("Sieben"), vec![3]);
+        assert_eq!(index.search("bi"), vec![1, 2]);
+        // Only the start of a word counts
+        assert!(index.search("schlaefer").is_empty());
+    }
+
+    #[test]
+    fn umlauts_match_however_they_are_typed() {
+        let index = SearchIndex::build(&[track(1, "Die Schöne Müllerin", ROOT_LIBRARY_ENTRY_ID)]);
+
+        for query in ["Schöne", "schoene", "SCHOENE", "müll", "muell"] {
+            assert_eq!(index.search(query), vec![1], "{}", query);
+        }
+        assert!(index.search("schone").is_empty());
+    }
+
+    #[test]
+    fn every_word_has_to_match_and_names_come_first() {
+        let index = SearchIndex::build(&[
+            folder(1, "Pumuckl", ROOT_LIBRARY_ENTRY_ID),
+            track(2, "Pumuckl und der Geburtstag", 1),
+            track(3, "Der Geburtstag", 1),
+        ]);
+
+        // Found in its own name beats found in the folder's
+        assert_eq!(index.search("pumuckl"), vec![1, 2, 3]);
+        assert_eq!(index.search("pumuckl geburtstag"), vec![2, 3]);
+        assert!(index.search("pumuckl weihnachten").is_empty());
+        assert!(index.search("").is_empty());
+    }
+}
@@ -0,0 +1,32 @@
+//! Shared by the tests, they run against the same library, player and state as the app
+
+use std::{path::PathBuf, sync::Once};
+
+use crate::{mock::Player, model::State};
+
+/// The PIN of [`state`]
+pub const PIN: &str = "1234";
+
+/// Where this test run keeps its files
+pub fn directory() -> PathBuf {
+    std::env::temp_dir().join(format!("rust-slint-music-player-test-{}", std::process::id()))
+}
+
+/// Points the data, cache and config directories at a fresh one for this test run, before
+/// anything loads the library. Every test calls this first, the user's own library stays as it is.
+pub fn isolate_directories() {
+    static ISOLATED: Once = Once::new();
+    ISOLATED.call_once(|| {
+        let directory = directory();
+        std::fs::remove_dir_all(&directory).ok();
+        std::env::set_var("XDG_DATA_HOME", directory.join("data"));
+        std::env::set_var("XDG_CACHE_HOME", directory.join("cache"));
+        std::env::set_var("XDG_CONFIG_HOME", directory.join("config"));
+    });
+}
+
+/// A state without profiles, must be called from within a tokio runtime like [`State::new`]
+pub fn state() -> State {
+    isolate_directories();
+    State::new(Player::new(), Some(PIN.to_string()), vec![])
+}
@@ -0,0 +1,119 @@
+use slint::{ComponentHandle, Image, Weak};
+
+use crate::{
+    images,
+    model::{actions::Unlock, Action, Field, State},
+    view_model::pin_pad::ask_for_pin,
+    AppWindow, ChildLock, ContextMenu, Edit, Search,
+};
+
+/// Edge length of the cover on the lock screen
+const COVER_SIZE: u32 = 160;
+
+/// The corners in `Unlock::Gesture` order
+const CORNER_ARROWS: [&str; 4] = ["↖", "↗", "↘", "↙"];
+
+pub struct ChildLockVM {
+    ui: Weak<AppWindow>,
+    state: State,
+}
+
+impl ChildLockVM {
+    pub fn new(ui: Weak<AppWindow>, state: State) -> Self {
+        let vm = ChildLockVM { ui, state };
+        vm.setup_ui();
+        vm.setup_state_listeners();
+        vm
+    }
+
+    pub fn setup_ui(&self) {
+        if let Some(ui) = self.ui.upgrade() {
+            let child_lock = ui.global::<ChildLock>();
+            child_lock.set_volume(self.state.volume());
+            let gesture = self.state.unlock_gesture();
+            child_lock.set_gesture_length(gesture.len() as i32);
+            child_lock.set_gesture(gesture.iter().map(|&corner| CORNER_ARROWS[corner as usize]).collect::<Vec<_>>().join(" ").into());
+
+            {
+                let state_ = self.state.clone();
+                child_lock.on_lock(move || state_.dispatch(Action::Lock));
+            }
+
+            {
+                let state_ = self.state.clone();
+                child_lock.on_toggle_play(move || state_.dispatch(Action::TogglePlay(state_.is_playing())));
+            }
+
+            {
+                let state_ = self.state.clone();
+                child_lock.on_change_volume(move |step| state_.dispatch(Action::SetVolume(state_.volume() + step)));
+            }
+
+            {
+                let ui_ = self.ui.clone();
+                child_lock.on_ask_pin(move || {
+                    if let Some(ui) = ui_.upgrade() {
+                        ask_for_pin(&ui, "unlock", "PIN zum Entsperren");
+                    }
+                });
+            }
+
+            {
+                let state_ = self.state.clone();
+                child_lock.on_gesture_unlock(move |corners| {
+                    let corners = corners.bytes().map(|digit| digit - b'0').collect();
+                    state_.dispatch(Action::Unlock(Unlock::Gesture(corners)));
+                });
+            }
+        }
+    }
+
+    pub fn setup_state_listeners(&self) {
+        let ui_weak = self.ui.clone();
+        let state = self.state.clone();
+
+        self.state.subscribe(move |changes| {
+            if !changes.iter().any(|field| matches!(
+                field,
+                Field::locked(_) | Field::volume(_) | Field::is_playing(_) | Field::playing_library_entry(_)
+            )) {
+                return;
+            }
+
+            let locked = state.locked();
+            let volume = state.volume();
+            let is_playing = state.is_playing();
+            let playing = state.playing_library_entry();
+            let name = playing.as_ref().map(|entry| entry.name.clone()).unwrap_or_default();
+            let cover = playing.and_then(|entry| entry.image)
+                .and_then(|bytes| images::thumbnail(&bytes, COVER_SIZE).ok());
+
+            let ui_weak = ui_weak.clone();
+            slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_weak.upgrade() {
+                    let child_lock = ui.global::<ChildLock>();
+                    if locked && !child_lock.get_locked() {
+                        // Whatever was open stays behind the lock screen, and is gone once unlocked
+                        ui.global::<Search>().set_visible(false);
+                        ui.global::<ContextMenu>().set_visible(false);
+                        ui.global::<Edit>().set_menu_visible(false);
+                    }
+                    child_lock.set_locked(locked);
+                    child_lock.set_volume(volume);
+                    child_lock.set_is_playing(is_playing);
+                    child_lock.set_playing_name(name.into());
+                    child_lock.set_playing_image(cover.map(Image::from_rgba8).unwrap_or_default());
+                }
+            }).ok();
+        });
+    }
+}
+
+impl Clone for ChildLockVM {
+    fn clone(&self) -> Self {
+        ChildLockVM {
+            ui: self.ui.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
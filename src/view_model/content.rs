@@ -1,7 +1,11 @@
-use crate::{model::State, AppWindow, Content};
-use slint::{ComponentHandle, ModelRc, VecModel, Weak};
-use crate::mock::LibraryEntry;
+use crate::{model::{Action, State}, AppWindow, Content, ContextMenu, Edit};
+use std::collections::HashMap;
+
+use slint::{ComponentHandle, Image, Model, ModelRc, VecModel, Weak};
+use crate::images::{self, Thumbnail};
+use crate::mock::{LibraryEntry, SortOrder, Variant, ViewMode};
 use crate::model::Field;
+use crate::view_model::dates;
 
 pub struct ContentVM {
     ui: Weak<AppWindow>,
@@ -21,9 +25,110 @@ impl ContentVM {
             let content = ui.global::<Content>();
 
             {
-                let state_ = self.state.clone();
+                let (ui_, state_) = (self.ui.clone(), self.state.clone());
                 content.on_select_library_entry(move |id| {
-                    state_.dispatch(crate::model::actions::Action::LoadLibraryEntry(id));
+                    // While editing, folders still open but everything else gets the edit menu
+                    if state_.edit_mode() {
+                        let is_folder = state_.active_library_entry()
+                            .and_then(|entry| entry.children)
+                            .is_some_and(|children| children.iter().any(|child| child.id == id && child.variant.opens()));
+                        if !is_folder {
+                            if let Some(ui) = ui_.upgrade() {
+                                ui.global::<Edit>().invoke_open_entry(id);
+                            }
+                            return;
+                        }
+                    }
+                    navigate(&ui_, &state_, Action::SelectLibraryEntry(id));
+                });
+            }
+
+            {
+                let (ui_, state_) = (self.ui.clone(), self.state.clone());
+                content.on_open_context_menu(move |id| {
+                    let entry = state_.active_library_entry()
+                        .and_then(|entry| entry.children)
+                        .and_then(|children| children.into_iter().find(|child| child.id == id));
+                    // Everything in the menu is kept with the entry, virtual ones have nowhere to keep it
+                    let Some(entry) = entry.filter(|entry| !entry.is_virtual()) else {
+                        return;
+                    };
+                    let is_folder = entry.variant.opens();
+                    // Folders have nothing to queue or resume
+                    let can_resume = !is_folder && (state_.resume_positions().contains_key(&id)
+                        || state_.playing_library_entry().is_some_and(|playing| playing.id == id));
+
+                    if let Some(ui) = ui_.upgrade() {
+                        let context_menu = ui.global::<ContextMenu>();
+                        context_menu.set_entry_id(id);
+                        context_menu.set_entry_name(entry.name.into());
+                        context_menu.set_can_resume(can_resume);
+                        context_menu.set_is_folder(is_folder);
+                        context_menu.set_is_favourite(entry.favourite);
+                        context_menu.set_is_pinned(entry.pinned);
+                        context_menu.set_view_mode(match entry.view_mode {
+                            Some(ViewMode::Tiles) => "tiles",
+                            Some(ViewMode::List) => "list",
+                            None => "",
+                        }.into());
+                        context_menu.set_sort_order(match entry.sort_order {
+                            SortOrder::Manual => "manual",
+                            SortOrder::Name => "name",
+                            SortOrder::RecentlyPlayed => "recently-played",
+                            SortOrder::DateAdded => "date-added",
+                        }.into());
+                        context_menu.set_visible(true);
+                    }
+                });
+            }
+
+            let context_menu = ui.global::<ContextMenu>();
+            {
+                let state_ = self.state.clone();
+                context_menu.on_play_next(move |id| state_.dispatch(Action::PlayNext(id)));
+            }
+            {
+                let state_ = self.state.clone();
+                context_menu.on_add_to_queue(move |id| state_.dispatch(Action::AddToQueue(id)));
+            }
+            {
+                let state_ = self.state.clone();
+                context_menu.on_resume(move |id| state_.dispatch(Action::ResumeLibraryEntry(id)));
+            }
+            {
+                let state_ = self.state.clone();
+                context_menu.on_restart(move |id| state_.dispatch(Action::RestartLibraryEntry(id)));
+            }
+            {
+                let state_ = self.state.clone();
+                context_menu.on_toggle_favourite(move |id| state_.dispatch(Action::ToggleFavourite(id)));
+            }
+            {
+                let state_ = self.state.clone();
+                context_menu.on_toggle_pinned(move |id| state_.dispatch(Action::TogglePinned(id)));
+            }
+            {
+                let (ui_, state_) = (self.ui.clone(), self.state.clone());
+                context_menu.on_set_view_mode(move |id, view_mode| {
+                    let view_mode = match view_mode.as_str() {
+                        "tiles" => Some(ViewMode::Tiles),
+                        "list" => Some(ViewMode::List),
+                        _ => None,
+                    };
+                    // The shown folder is reloaded, keep it where it was scrolled to
+                    navigate(&ui_, &state_, Action::SetViewMode(id, view_mode));
+                });
+            }
+            {
+                let (ui_, state_) = (self.ui.clone(), self.state.clone());
+                context_menu.on_set_sort_order(move |id, sort_order| {
+                    let sort_order = match sort_order.as_str() {
+                        "name" => SortOrder::Name,
+                        "recently-played" => SortOrder::RecentlyPlayed,
+                        "date-added" => SortOrder::DateAdded,
+                        _ => SortOrder::Manual,
+                    };
+                    navigate(&ui_, &state_, Action::SetSortOrder(id, sort_order));
                 });
             }
         }
@@ -36,103 +141,167 @@ impl ContentVM {
         
         // Subscribe to state changes
         self.state.subscribe(move |changes| {
-            let has_active_library_entry = changes.iter().any(|field| matches!(field, Field::active_library_entry(_)));
-            match change.changed_fields.as_slice() {
-                fields if fields.contains(&"active_library_entry".to_string()) => {
-                    // Update the UI with the new library entry
-                    let entry = state.active_library_entry();
-                    let ui_weak = ui_weak.clone();
-
-                    if entry.is_none() {
-                        println!("No library entry available");
+            if !changes.iter().any(|field| matches!(field, Field::active_library_entry(_))) {
+                return;
+            }
+
+            // Update the UI with the new library entry
+            let entry = state.active_library_entry();
+            let scroll_offset = state.navigation().current.map(|location| location.scroll_offset).unwrap_or_default();
+            let ui_weak = ui_weak.clone();
+
+            if entry.is_none() {
+                return;
+            }
+
+            // Decode the covers here, the UI thread only wraps the finished pixel buffers
+            let thumbnails = entry.as_ref()
+                .and_then(|entry| entry.children.as_deref())
+                .map(|children| images::artwork_for(children, images::TILE_IMAGE_SIZE))
+                .unwrap_or_default();
+
+            // Only navigating restores the remembered offset, reloading the same entry stays put
+            let navigated = changes.iter().any(|field| matches!(field, Field::navigation(_)));
+            let view_mode = entry.as_ref().and_then(|entry| entry.view_mode);
+            let markers = PlaybackMarkers::from_state(&state);
+            // UI Updates müssen auf dem Hauptthread stattfinden
+            slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_weak.upgrade() {
+                    let content = ui.global::<Content>();
+
+                    let children = entry.unwrap().children;
+                    if children.is_none() {
                         return;
                     }
 
-                    let state = state.clone();
-                    // UI Updates müssen auf dem Hauptthread stattfinden
-                    slint::invoke_from_event_loop(move || {
-                        if let Some(ui) = ui_weak.upgrade() {
-                            println!("UI upgrade successful!");
-                            let content = ui.global::<Content>();
-                            
-                            let children = entry.unwrap().children;
-                            if children.is_none() {
-                                return;
-                            }
+                    let children = children.unwrap();
+                    let (tiles, rows) = Self::split_children(view_mode, children);
+                    let layout = match (tiles.is_empty(), rows.is_empty()) {
+                        (false, false) => "sections",
+                        (true, false) => "list",
+                        _ => "tiles",
+                    };
+                    content.set_layout(layout.into());
+                    Self::set_view_data(&content, tiles, rows, &thumbnails, &markers);
+                    if navigated {
+                        content.set_scroll_offset(scroll_offset);
+                    }
+                }
+            }).ok();
+        });
 
-                            let children = children.unwrap();
-                            let variant = children.first().map(|e| format!("{:?}", e.variant)).unwrap_or_default();
-                            content.set_variant(variant.to_owned().into());
+        // Playback changes only touch the markers, the lists stay as they are
+        let ui_weak = self.ui.clone();
+        let state = self.state.clone();
+        self.state.subscribe(move |changes| {
+            let playback_changed = changes.iter().any(|field| matches!(
+                field,
+                Field::is_playing(_) | Field::playing_library_entry(_) | Field::progress(_)
+            ));
+            if !playback_changed {
+                return;
+            }
 
-                            if variant == "folder" || variant == "stream" {
-                                println!("Setting tile view data");
-                                Self::set_tile_view_data(&content, children, state);
-                            } else {
-                                println!("Setting detail view data");
-                                Self::set_detail_view_data(&content, children, state);
-                            }
-                        } else {
-                            println!("UI has been dropped");
-                        }
-                    }).ok();
+            let markers = PlaybackMarkers::from_state(&state);
+            let ui_weak = ui_weak.clone();
+            slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_weak.upgrade() {
+                    Self::update_playback_markers(&ui.global::<Content>(), &markers);
                 }
-                _ => {}
-            }
+            }).ok();
         });
     }
 
-    fn set_tile_view_data(ui: &Content<'_>, entries: Vec<LibraryEntry>, state: State) {
-        // Convert entries to the format expected by the UI
-        let rows_data: Vec<Vec<_>> = entries
-            .chunks(3) // Group into rows of 3 items
-            .map(|chunk| {
-                chunk.iter().map(|entry| Self::map_library_entry_to_ui(entry, &state)).collect()
-            })
-            .collect();
-        
-        let rows_model = ModelRc::new(VecModel::from(
-            rows_data.into_iter()
-                .map(|row| ModelRc::new(VecModel::from(row)))
-                .collect::<Vec<_>>()
-        ));
-        
-        ui.set_tile_rows(rows_model);
-        ui.set_detail_rows(ModelRc::default());
+    /// Tiles for what opens or streams, rows for tracks, unless the folder asks for one view only
+    fn split_children(view_mode: Option<ViewMode>, children: Vec<LibraryEntry>) -> (Vec<LibraryEntry>, Vec<LibraryEntry>) {
+        match view_mode {
+            Some(ViewMode::Tiles) => (children, vec![]),
+            Some(ViewMode::List) => (vec![], children),
+            None => children.into_iter().partition(|child| child.variant.opens() || child.variant == Variant::Stream),
+        }
     }
 
-    fn set_detail_view_data(ui: &Content<'_>, entries: Vec<LibraryEntry>, state: State) {
+    fn set_view_data(ui: &Content<'_>, tiles: Vec<LibraryEntry>, rows: Vec<LibraryEntry>, thumbnails: &HashMap<i32, Thumbnail>, markers: &PlaybackMarkers) {
         // Convert entries to the format expected by the UI
-        let rows_data: Vec<_> = entries.iter().map(|entry| Self::map_library_entry_to_ui(entry, &state)).collect();
-        
-        let rows_model = ModelRc::new(VecModel::from(rows_data));
-        
-        ui.set_tile_rows(ModelRc::default());
-        ui.set_detail_rows(rows_model);
+        let tiles_data: Vec<_> = tiles.iter().map(|entry| Self::map_library_entry_to_ui(entry, thumbnails, markers)).collect();
+        let rows_data: Vec<_> = rows.iter().map(|entry| Self::map_library_entry_to_ui(entry, thumbnails, markers)).collect();
+
+        ui.set_tiles(ModelRc::new(VecModel::from(tiles_data)));
+        ui.set_detail_rows(ModelRc::new(VecModel::from(rows_data)));
+    }
+
+    /// Patches the playback markers of the rows that changed, leaving the rest of the models and
+    /// their images alone
+    fn update_playback_markers(ui: &Content<'_>, markers: &PlaybackMarkers) {
+        for model in [ui.get_tiles(), ui.get_detail_rows()] {
+            for row in 0..model.row_count() {
+                if let Some(mut entry) = model.row_data(row) {
+                    if markers.apply(&mut entry) {
+                        model.set_row_data(row, entry);
+                    }
+                }
+            }
+        }
     }
 
-    fn map_library_entry_to_ui(entry: &LibraryEntry, state: &State) -> crate::UILibraryEntry {
-        crate::UILibraryEntry {
+    fn map_library_entry_to_ui(entry: &LibraryEntry, thumbnails: &HashMap<i32, Thumbnail>, markers: &PlaybackMarkers) -> crate::UILibraryEntry {
+        let mut ui_entry = crate::UILibraryEntry {
             id: entry.id,
             parent_id: entry.parent_id.unwrap_or(0),
             variant: format!("{:?}", entry.variant).into(),
             name: entry.name.clone().into(),
             played_at: entry.played_at
-                .map(|dt| dt.format("%Y-%m-%d").to_string())
+                .map(|played_at| dates::relative_date(played_at, chrono::Utc::now()))
                 .unwrap_or_default().into(),
-            image: entry.image.clone()
-                .map(|img| ModelRc::new(VecModel::from(
-                    img.into_iter().map(|b| b as i32).collect::<Vec<_>>()
-                )))
-                .unwrap_or_else(|| ModelRc::new(VecModel::default())),
+            image: thumbnails.get(&entry.id)
+                .map(|thumbnail| Image::from_rgba8(thumbnail.clone()))
+                .unwrap_or_default(),
             sort_key: entry.sort_key,
+            is_favourite: entry.favourite,
+            unplayed: entry.unplayed,
+            ..Default::default()
+        };
+        markers.apply(&mut ui_entry);
+        ui_entry
+    }
+}
+
+/// What the lists need to know to mark the loaded entry
+struct PlaybackMarkers {
+    loaded_id: Option<i32>,
+    is_playing: bool,
+    play_progress: i32,
+}
+
+impl PlaybackMarkers {
+    fn from_state(state: &State) -> Self {
+        PlaybackMarkers {
+            loaded_id: state.playing_library_entry().map(|entry| entry.id),
             is_playing: state.is_playing(),
-            is_loaded: match state.playing_library_entry() {
-                Some(playing_library_entry) if playing_library_entry.id == entry.id => true,
-                _ => false,
-            },
-            play_progress: 0,
+            play_progress: (state.progress().fraction() * 100.0).round() as i32,
         }
     }
+
+    /// Returns whether anything changed
+    fn apply(&self, entry: &mut crate::UILibraryEntry) -> bool {
+        let is_loaded = self.loaded_id == Some(entry.id);
+        let is_playing = is_loaded && self.is_playing;
+        let play_progress = if is_loaded { self.play_progress } else { 0 };
+
+        let changed = entry.is_loaded != is_loaded || entry.is_playing != is_playing || entry.play_progress != play_progress;
+        entry.is_loaded = is_loaded;
+        entry.is_playing = is_playing;
+        entry.play_progress = play_progress;
+        changed
+    }
+}
+
+/// Navigates away from the current entry after remembering how far it was scrolled
+pub fn navigate(ui: &Weak<AppWindow>, state: &State, action: Action) {
+    if let Some(ui) = ui.upgrade() {
+        state.dispatch(Action::SetScrollOffset(ui.global::<Content>().get_scroll_offset()));
+    }
+    state.dispatch(action);
 }
 
 impl Clone for ContentVM {
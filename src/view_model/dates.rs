@@ -0,0 +1,55 @@
+use chrono::{DateTime, Local, Utc};
+
+/// "heute", "gestern", "vor 3 Tagen" and so on, counted in calendar days of the local time zone
+pub fn relative_date(date: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let date = date.with_timezone(&Local).date_naive();
+    let days = (now.with_timezone(&Local).date_naive() - date).num_days();
+    match days {
+        // Clocks of devices without network can be off, the future is treated as today
+        ..=0 => "heute".to_string(),
+        1 => "gestern".to_string(),
+        2 => "vorgestern".to_string(),
+        3..=6 => format!("vor {} Tagen", days),
+        7..=13 => "letzte Woche".to_string(),
+        14..=30 => format!("vor {} Wochen", days / 7),
+        _ => date.format("%d.%m.%Y").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Local time, whatever zone the tests run in
+    fn local(year: i32, month: u32, day: u32, hour: u32) -> DateTime<Utc> {
+        Local.with_ymd_and_hms(year, month, day, hour, 0, 0).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn names_the_last_few_days() {
+        let now = local(2024, 3, 20, 12);
+
+        assert_eq!(relative_date(local(2024, 3, 20, 8), now), "heute");
+        assert_eq!(relative_date(local(2024, 3, 19, 12), now), "gestern");
+        assert_eq!(relative_date(local(2024, 3, 18, 12), now), "vorgestern");
+        assert_eq!(relative_date(local(2024, 3, 17, 12), now), "vor 3 Tagen");
+        assert_eq!(relative_date(local(2024, 3, 14, 12), now), "vor 6 Tagen");
+        assert_eq!(relative_date(local(2024, 3, 13, 12), now), "letzte Woche");
+        assert_eq!(relative_date(local(2024, 3, 7, 12), now), "letzte Woche");
+        assert_eq!(relative_date(local(2024, 3, 6, 12), now), "vor 2 Wochen");
+        assert_eq!(relative_date(local(2024, 2, 19, 12), now), "vor 4 Wochen");
+        assert_eq!(relative_date(local(2024, 2, 18, 12), now), "18.02.2024");
+    }
+
+    #[test]
+    fn counts_calendar_days_not_hours() {
+        // Two hours apart but across midnight
+        assert_eq!(relative_date(local(2024, 3, 19, 23), local(2024, 3, 20, 1)), "gestern");
+    }
+
+    #[test]
+    fn treats_the_future_as_today() {
+        assert_eq!(relative_date(local(2024, 3, 22, 12), local(2024, 3, 20, 12)), "heute");
+    }
+}
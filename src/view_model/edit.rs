@@ -0,0 +1,216 @@
+use slint::{ComponentHandle, Image, ModelRc, VecModel, Weak};
+
+use crate::{
+    images::{self, Thumbnail},
+    mock::{self, LibraryEntry, Variant, ROOT_LIBRARY_ENTRY_ID},
+    model::{Action, Field, State},
+    view_model::pin_pad::ask_for_pin,
+    AppWindow, Edit, UICoverChoice, UIFolderChoice,
+};
+
+/// Edge length of the cover choices in the edit menu
+const COVER_CHOICE_SIZE: u32 = 96;
+
+pub struct EditVM {
+    ui: Weak<AppWindow>,
+    state: State,
+}
+
+impl EditVM {
+    pub fn new(ui: Weak<AppWindow>, state: State) -> Self {
+        let vm = EditVM { ui, state };
+        vm.setup_ui();
+        vm.setup_state_listeners();
+        vm
+    }
+
+    pub fn setup_ui(&self) {
+        if let Some(ui) = self.ui.upgrade() {
+            let edit = ui.global::<Edit>();
+
+            {
+                let ui_ = self.ui.clone();
+                edit.on_unlock(move || {
+                    if let Some(ui) = ui_.upgrade() {
+                        ask_for_pin(&ui, "edit", "PIN zum Bearbeiten");
+                    }
+                });
+            }
+
+            {
+                let state_ = self.state.clone();
+                edit.on_leave(move || state_.dispatch(Action::LeaveEditMode));
+            }
+
+            {
+                let state_ = self.state.clone();
+                edit.on_undo(move || state_.dispatch(Action::UndoEdit));
+            }
+
+            {
+                let (ui_, state_) = (self.ui.clone(), self.state.clone());
+                edit.on_open_entry(move |id| {
+                    let entry = state_.active_library_entry()
+                        .and_then(|entry| entry.children)
+                        .and_then(|children| children.into_iter().find(|child| child.id == id));
+                    // Virtual folders like the recently played one are not part of the library
+                    let Some(entry) = entry.filter(|entry| entry.id >= 0) else {
+                        return;
+                    };
+
+                    // Going through the library and decoding the covers takes a while on a Pi, the
+                    // UI thread only gets the finished choices
+                    let ui_ = ui_.clone();
+                    std::thread::spawn(move || {
+                        let folders = Self::folder_choices(&mock::scan_library(), &entry);
+                        let covers = Self::cover_choices();
+                        slint::invoke_from_event_loop(move || {
+                            if let Some(ui) = ui_.upgrade() {
+                                let covers: Vec<_> = covers.into_iter()
+                                    .map(|(name, thumbnail)| UICoverChoice { name: name.into(), image: Image::from_rgba8(thumbnail) })
+                                    .collect();
+                                let edit = ui.global::<Edit>();
+                                edit.set_entry_id(id);
+                                edit.set_entry_name(entry.name.into());
+                                edit.set_folders(ModelRc::new(VecModel::from(folders)));
+                                edit.set_covers(ModelRc::new(VecModel::from(covers)));
+                                edit.set_page("menu".into());
+                                edit.set_menu_visible(true);
+                            }
+                        }).ok();
+                    });
+                });
+            }
+
+            {
+                let ui_ = self.ui.clone();
+                edit.on_type_text(move |text| {
+                    Self::edit_name(&ui_, |name| name.push_str(&text));
+                });
+            }
+
+            {
+                let ui_ = self.ui.clone();
+                edit.on_backspace(move || {
+                    Self::edit_name(&ui_, |name| {
+                        name.pop();
+                    });
+                });
+            }
+
+            {
+                let state_ = self.state.clone();
+                edit.on_rename(move |id, name| state_.dispatch(Action::RenameLibraryEntry(id, name.to_string())));
+            }
+
+            {
+                let state_ = self.state.clone();
+                edit.on_move_to(move |id, parent_id| state_.dispatch(Action::MoveLibraryEntry(id, parent_id)));
+            }
+
+            {
+                let state_ = self.state.clone();
+                edit.on_pick_cover(move |id, name| state_.dispatch(Action::PickCover(id, name.to_string())));
+            }
+
+            {
+                let state_ = self.state.clone();
+                edit.on_delete_entry(move |id| state_.dispatch(Action::DeleteLibraryEntry(id)));
+            }
+
+            {
+                let state_ = self.state.clone();
+                edit.on_reorder(move |id, target_id| {
+                    if id != target_id {
+                        state_.dispatch(Action::ReorderLibraryEntry(id, target_id));
+                    }
+                });
+            }
+        }
+    }
+
+    pub fn setup_state_listeners(&self) {
+        let ui_weak = self.ui.clone();
+        let state = self.state.clone();
+
+        self.state.subscribe(move |changes| {
+            if !changes.iter().any(|field| matches!(field, Field::edit_mode(_) | Field::undo_stack(_))) {
+                return;
+            }
+
+            let edit_mode = state.edit_mode();
+            let undo_label = state.undo_stack().last().map(|step| step.label.clone()).unwrap_or_default();
+            let ui_weak = ui_weak.clone();
+            slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_weak.upgrade() {
+                    let edit = ui.global::<Edit>();
+                    edit.set_active(edit_mode);
+                    edit.set_undo_label(undo_label.into());
+                    if !edit_mode {
+                        edit.set_menu_visible(false);
+                    }
+                }
+            }).ok();
+        });
+    }
+
+    /// The new name is typed on the UI side, the model only gets the finished one
+    fn edit_name(ui: &Weak<AppWindow>, edit: impl FnOnce(&mut String)) {
+        if let Some(ui) = ui.upgrade() {
+            let global = ui.global::<Edit>();
+            let mut name = global.get_new_name().to_string();
+            edit(&mut name);
+            global.set_new_name(name.into());
+        }
+    }
+
+    /// Every folder in tree order, leaving out where the entry already is and, for folders, the
+    /// folder itself with everything inside it
+    fn folder_choices(entries: &[LibraryEntry], moving: &LibraryEntry) -> Vec<UIFolderChoice> {
+        let mut choices = vec![];
+        let mut pending = vec![(ROOT_LIBRARY_ENTRY_ID, 0)];
+        while let Some((id, depth)) = pending.pop() {
+            let Some(folder) = entries.iter().find(|e| e.id == id) else {
+                continue;
+            };
+            if folder.id == moving.id {
+                continue;
+            }
+            if Some(folder.id) != moving.parent_id {
+                choices.push(UIFolderChoice {
+                    id: folder.id,
+                    name: if folder.id == ROOT_LIBRARY_ENTRY_ID { "Home".into() } else { folder.name.clone().into() },
+                    depth,
+                });
+            }
+
+            let mut children: Vec<&LibraryEntry> = entries.iter()
+                .filter(|e| e.parent_id == Some(folder.id) && e.variant == Variant::Folder)
+                .collect();
+            children.sort_by(|a, b| a.sort_key.cmp(&b.sort_key).then_with(|| a.name.cmp(&b.name)));
+            // Reversed, the stack hands out the first child next
+            pending.extend(children.iter().rev().map(|child| (child.id, depth + 1)));
+        }
+        choices
+    }
+
+    /// The images folder's covers by name, decoded but not yet wrapped, images stay on the UI thread
+    fn cover_choices() -> Vec<(String, Thumbnail)> {
+        mock::cover_images().into_iter()
+            .filter_map(|name| {
+                let bytes = mock::cover_image(&name)?;
+                let thumbnail = images::thumbnail(&bytes, COVER_CHOICE_SIZE).ok()?;
+                Some((name, thumbnail))
+            })
+            .collect()
+    }
+}
+
+impl Clone for EditVM {
+    fn clone(&self) -> Self {
+        EditVM {
+            ui: self.ui.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use slint::{ComponentHandle, Weak};
-use crate::{AppWindow, Messages};
-use crate::model::State;
+use crate::{AppWindow, Messages, UIMessage};
+use crate::model::{Action, Field, Message, Severity, State};
 
 pub struct MessagesVM {
     ui: Weak<AppWindow>,
@@ -16,11 +16,28 @@ impl MessagesVM {
     }
 
     pub fn setup_ui(&self) {
-        // Setup UI logic here
         if let Some(ui) = self.ui.upgrade() {
             let messages_global = ui.global::<Messages>();
             // Initialize with empty data
             messages_global.set_messages(slint::ModelRc::new(slint::VecModel::default()));
+
+            {
+                let state_ = self.state.clone();
+                messages_global.on_dismiss(move |id| {
+                    state_.dispatch(Action::DismissMessage(id as u64));
+                });
+            }
+
+            {
+                let state_ = self.state.clone();
+                messages_global.on_activate(move |id| {
+                    let message = state_.messages().into_iter().find(|message| message.id == id as u64);
+                    if let Some(action) = message.and_then(|message| message.action) {
+                        state_.dispatch(*action.action);
+                    }
+                    state_.dispatch(Action::DismissMessage(id as u64));
+                });
+            }
         }
     }
 
@@ -28,40 +45,44 @@ impl MessagesVM {
         // Clone for move closure
         let ui_weak = self.ui.clone();
         let state_clone = self.state.clone();
-        
+
         // Subscribe to state changes
-        self.state.subscribe(move |change| {
-            println!("State changed: {:?}", change);
-            match change.changed_fields.as_slice() {
-                fields if fields.contains(&"messages".to_string()) => {
-                    // Clone again for the event loop closure
-                    let ui_weak_clone = ui_weak.clone();
-                    let state_clone_inner = state_clone.clone();
-                        
-                    slint::invoke_from_event_loop(move || {
-                        // Update the UI with the new messages
-                        let messages = state_clone_inner.messages();
-                        if let Some(ui) = ui_weak_clone.upgrade() {
-                            println!("UI has been upgraded");
-                            let messages_global = ui.global::<Messages>();
-                            
-                            // Convert messages to SharedString format
-                            let messages_data: Vec<slint::SharedString> = messages
-                                .iter()
-                                .map(|msg| msg.clone().into())
-                                .collect();
-                            
-                            messages_global.set_messages(slint::ModelRc::new(slint::VecModel::from(messages_data)));
-                            println!("Messages updated in UI");
-                        } else {
-                            println!("UI has been dropped");
-                        }
-                    }).ok();
-                }
-                _ => {}
+        self.state.subscribe(move |changes| {
+            if !changes.iter().any(|field| matches!(field, Field::messages(_))) {
+                return;
             }
+
+            // Clone again for the event loop closure
+            let ui_weak_clone = ui_weak.clone();
+            let messages = state_clone.messages();
+
+            slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_weak_clone.upgrade() {
+                    let messages_global = ui.global::<Messages>();
+
+                    let messages_data: Vec<UIMessage> = messages.iter().map(Self::map_message_to_ui).collect();
+
+                    messages_global.set_messages(slint::ModelRc::new(slint::VecModel::from(messages_data)));
+                } else {
+                    println!("UI has been dropped");
+                }
+            }).ok();
         });
     }
+
+    fn map_message_to_ui(message: &Message) -> UIMessage {
+        UIMessage {
+            id: message.id as i32,
+            severity: match message.severity {
+                Severity::Info => "info",
+                Severity::Warning => "warning",
+                Severity::Error => "error",
+            }.into(),
+            text: message.text.clone().into(),
+            count: message.count as i32,
+            action_label: message.action.as_ref().map(|action| action.label.clone()).unwrap_or_default().into(),
+        }
+    }
 }
 
 impl Clone for MessagesVM {
@@ -71,4 +92,4 @@ impl Clone for MessagesVM {
             state: self.state.clone(),
         }
     }
-}
\ No newline at end of file
+}
@@ -1,3 +1,10 @@
+pub mod child_lock;
 pub mod content;
+pub mod dates;
+pub mod edit;
 pub mod messages;
-pub mod navbar;
\ No newline at end of file
+pub mod navbar;
+pub mod pin_pad;
+pub mod profiles;
+pub mod search;
+pub mod time_up;
\ No newline at end of file
@@ -1,6 +1,6 @@
-use slint::{ComponentHandle, Weak};
+use slint::{ComponentHandle, ModelRc, VecModel, Weak};
 
-use crate::{model::State, AppWindow, Navbar};
+use crate::{mock::{LibraryEntry, ROOT_LIBRARY_ENTRY_ID}, model::{Action, Field, State}, view_model::content::navigate, AppWindow, Navbar, UIBreadcrumb};
 
 
 pub struct NavbarVM {
@@ -24,10 +24,23 @@ impl NavbarVM {
             navbar_global.set_visible(false);
 
             {
-                let state_ = self.state.clone();
-                navbar_global.on_go_back(move |parent_id| {
-                    state_.dispatch(crate::model::actions::Action::LoadLibraryEntry(parent_id));
-                });
+                let (ui_, state_) = (self.ui.clone(), self.state.clone());
+                navbar_global.on_go_back(move || navigate(&ui_, &state_, Action::GoBack));
+            }
+
+            {
+                let (ui_, state_) = (self.ui.clone(), self.state.clone());
+                navbar_global.on_select_breadcrumb(move |id| navigate(&ui_, &state_, Action::LoadLibraryEntry(id)));
+            }
+
+            {
+                let (ui_, state_) = (self.ui.clone(), self.state.clone());
+                navbar_global.on_go_forward(move || navigate(&ui_, &state_, Action::GoForward));
+            }
+
+            {
+                let (ui_, state_) = (self.ui.clone(), self.state.clone());
+                navbar_global.on_go_home(move || navigate(&ui_, &state_, Action::GoHome));
             }
         }
     }
@@ -38,36 +51,57 @@ impl NavbarVM {
         let state_clone = self.state.clone();
         
         // Subscribe to state changes
-        self.state.subscribe(move |change| {
-            match change.changed_fields.as_slice() {
-                fields if fields.contains(&"active_library_entry".to_string()) => {
-                    // Update the UI with the new library entry
-                    let entry = state_clone.active_library_entry();
-                    let ui_weak_clone = ui_weak.clone();
-
-                    if entry.is_none() {
-                        println!("No library entry available");
-                        return;
-                    }
-
-                    if entry.is_none() {
-                        println!("No library entry available");
-                        return;
-                    }
-
-                    let _ = slint::invoke_from_event_loop(move || {
-                        if let Some(ui) = ui_weak_clone.upgrade() {
-                            println!("UI upgrade successful!");
-                            let navbar = ui.global::<Navbar>();
-                            let entry = entry.unwrap();
-                            navbar.set_visible(entry.id != 0);
-                            navbar.set_entry_name(entry.name.into());
-                            navbar.set_parent_id(entry.parent_id.unwrap_or(0));
-                        }
-                    });
-                }
-                _ => {}
+        self.state.subscribe(move |changes| {
+            if !changes.iter().any(|field| matches!(field, Field::active_library_entry(_) | Field::navigation(_))) {
+                return;
             }
+
+            // Update the UI with the new library entry
+            let entry = state_clone.active_library_entry();
+            let can_go_forward = state_clone.navigation().can_go_forward();
+            // The profile button lives in the navbar, so it stays on home when there are profiles
+            let has_profiles = !state_clone.profiles().is_empty();
+            let ui_weak_clone = ui_weak.clone();
+
+            if entry.is_none() {
+                return;
+            }
+
+            let _ = slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_weak_clone.upgrade() {
+                    let navbar = ui.global::<Navbar>();
+                    let entry = entry.unwrap();
+                    navbar.set_visible(entry.id != ROOT_LIBRARY_ENTRY_ID || can_go_forward || has_profiles);
+                    navbar.set_can_go_forward(can_go_forward);
+                    navbar.set_breadcrumbs(ModelRc::new(VecModel::from(Self::breadcrumbs(&entry))));
+                }
+            });
         });
     }
-}
\ No newline at end of file
+
+    /// Root first path to the entry. Deep paths keep home and the last two crumbs so the bar
+    /// still fits the 800px screen next to the buttons, the rest collapses into an ellipsis. The
+    /// crumbs' width in the navbar is sized for no more than two names besides home.
+    fn breadcrumbs(entry: &LibraryEntry) -> Vec<UIBreadcrumb> {
+        const MAX_CRUMBS: usize = 3;
+
+        let mut crumbs: Vec<UIBreadcrumb> = entry.ancestors.clone().unwrap_or_default()
+            .into_iter()
+            .map(|ancestor| UIBreadcrumb {
+                id: ancestor.id,
+                name: if ancestor.id == ROOT_LIBRARY_ENTRY_ID { "Home".into() } else { ancestor.name.into() },
+                tappable: true,
+            })
+            .collect();
+        let name = if entry.id == ROOT_LIBRARY_ENTRY_ID { "Home".into() } else { entry.name.clone().into() };
+        crumbs.push(UIBreadcrumb { id: entry.id, name, tappable: false });
+
+        if crumbs.len() > MAX_CRUMBS {
+            let tail = crumbs.split_off(crumbs.len() - 2);
+            crumbs.truncate(1);
+            crumbs.push(UIBreadcrumb { id: -1, name: "…".into(), tappable: false });
+            crumbs.extend(tail);
+        }
+        crumbs
+    }
+}
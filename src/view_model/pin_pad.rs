@@ -0,0 +1,110 @@
+use slint::{ComponentHandle, Weak};
+
+use crate::{model::{actions::Unlock, Action, State}, AppWindow, PinPad};
+
+/// Longer PINs are not typed by parents but by kids hammering the pad
+const MAX_DIGITS: usize = 8;
+
+pub struct PinPadVM {
+    ui: Weak<AppWindow>,
+    state: State,
+}
+
+impl PinPadVM {
+    pub fn new(ui: Weak<AppWindow>, state: State) -> Self {
+        let vm = PinPadVM { ui, state };
+        vm.setup_ui();
+        vm
+    }
+
+    pub fn setup_ui(&self) {
+        if let Some(ui) = self.ui.upgrade() {
+            let pin_pad = ui.global::<PinPad>();
+
+            {
+                let ui_ = self.ui.clone();
+                pin_pad.on_key(move |digit| {
+                    Self::edit_digits(&ui_, |digits| {
+                        if digits.len() < MAX_DIGITS {
+                            digits.push_str(&digit);
+                        }
+                    });
+                });
+            }
+
+            {
+                let ui_ = self.ui.clone();
+                pin_pad.on_backspace(move || {
+                    Self::edit_digits(&ui_, |digits| {
+                        digits.pop();
+                    });
+                });
+            }
+
+            {
+                let ui_ = self.ui.clone();
+                pin_pad.on_cancel(move || {
+                    if let Some(ui) = ui_.upgrade() {
+                        Self::close(&ui.global::<PinPad>());
+                    }
+                });
+            }
+
+            {
+                let (ui_, state_) = (self.ui.clone(), self.state.clone());
+                pin_pad.on_confirm(move || {
+                    let Some(ui) = ui_.upgrade() else {
+                        return;
+                    };
+                    let pin_pad = ui.global::<PinPad>();
+                    let pin = pin_pad.get_digits().to_string();
+                    let purpose = pin_pad.get_purpose();
+                    Self::close(&pin_pad);
+                    // The model checks the PIN, a wrong one shows up as a message
+                    match purpose.as_str() {
+                        "edit" => state_.dispatch(Action::EnterEditMode(pin)),
+                        "unlock" => state_.dispatch(Action::Unlock(Unlock::Pin(pin))),
+                        other => match other.strip_prefix("extra:").and_then(|minutes| minutes.parse().ok()) {
+                            Some(minutes) => state_.dispatch(Action::GrantExtraMinutes(pin, minutes)),
+                            None => eprintln!("PIN entered for unknown purpose {:?}", other),
+                        },
+                    }
+                });
+            }
+        }
+    }
+
+    fn edit_digits(ui: &Weak<AppWindow>, edit: impl FnOnce(&mut String)) {
+        if let Some(ui) = ui.upgrade() {
+            let pin_pad = ui.global::<PinPad>();
+            let mut digits = pin_pad.get_digits().to_string();
+            edit(&mut digits);
+            pin_pad.set_dots("●".repeat(digits.len()).into());
+            pin_pad.set_digits(digits.into());
+        }
+    }
+
+    fn close(pin_pad: &PinPad<'_>) {
+        pin_pad.set_visible(false);
+        pin_pad.set_digits("".into());
+        pin_pad.set_dots("".into());
+    }
+}
+
+/// Asks for the PIN, `purpose` decides what the PIN is handed to once confirmed
+pub fn ask_for_pin(ui: &AppWindow, purpose: &str, title: &str) {
+    let pin_pad = ui.global::<PinPad>();
+    PinPadVM::close(&pin_pad);
+    pin_pad.set_purpose(purpose.into());
+    pin_pad.set_title(title.into());
+    pin_pad.set_visible(true);
+}
+
+impl Clone for PinPadVM {
+    fn clone(&self) -> Self {
+        PinPadVM {
+            ui: self.ui.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use slint::{ComponentHandle, ModelRc, VecModel, Weak};
+
+use crate::{
+    model::{Action, Field, State},
+    AppWindow, Profiles, UIProfile,
+};
+
+pub struct ProfilesVM {
+    ui: Weak<AppWindow>,
+    state: State,
+}
+
+impl ProfilesVM {
+    pub fn new(ui: Weak<AppWindow>, state: State) -> Self {
+        let vm = ProfilesVM { ui, state };
+        vm.setup_ui();
+        vm.setup_state_listeners();
+        vm
+    }
+
+    pub fn setup_ui(&self) {
+        if let Some(ui) = self.ui.upgrade() {
+            let profiles_global = ui.global::<Profiles>();
+            let profiles: Vec<UIProfile> = self.state.profiles().into_iter()
+                .map(|profile| UIProfile {
+                    initial: profile.name.chars().next().map(|c| c.to_uppercase().collect::<String>()).unwrap_or_default().into(),
+                    id: profile.id.into(),
+                    name: profile.name.into(),
+                })
+                .collect();
+            // Nobody has been picked yet, so ask who is listening before anything plays
+            profiles_global.set_visible(!profiles.is_empty() && self.state.profile().is_none());
+            profiles_global.set_profiles(ModelRc::new(VecModel::from(profiles)));
+
+            {
+                let state_ = self.state.clone();
+                profiles_global.on_pick(move |id| state_.dispatch(Action::SwitchProfile(id.into())));
+            }
+        }
+    }
+
+    pub fn setup_state_listeners(&self) {
+        let ui_weak = self.ui.clone();
+        let state = self.state.clone();
+
+        self.state.subscribe(move |changes| {
+            if !changes.iter().any(|field| matches!(field, Field::profile(_))) {
+                return;
+            }
+
+            let active_id = state.profile().map(|profile| profile.id).unwrap_or_default();
+            let ui_weak = ui_weak.clone();
+            slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_weak.upgrade() {
+                    ui.global::<Profiles>().set_active_id(active_id.into());
+                }
+            }).ok();
+        });
+    }
+}
+
+impl Clone for ProfilesVM {
+    fn clone(&self) -> Self {
+        ProfilesVM {
+            ui: self.ui.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use slint::{ComponentHandle, ModelRc, VecModel, Weak};
+
+use crate::{mock::{LibraryEntry, ROOT_LIBRARY_ENTRY_ID}, model::{Action, Field, State}, view_model::content::navigate, AppWindow, Search, UISearchResult};
+
+pub struct SearchVM {
+    ui: Weak<AppWindow>,
+    state: State,
+}
+
+impl SearchVM {
+    pub fn new(ui: Weak<AppWindow>, state: State) -> Self {
+        let vm = SearchVM { ui, state };
+        vm.setup_ui();
+        vm.setup_state_listeners();
+        vm
+    }
+
+    pub fn setup_ui(&self) {
+        if let Some(ui) = self.ui.upgrade() {
+            let search = ui.global::<Search>();
+
+            {
+                let (ui_, state_) = (self.ui.clone(), self.state.clone());
+                search.on_open(move || {
+                    if let Some(ui) = ui_.upgrade() {
+                        let search = ui.global::<Search>();
+                        search.set_query("".into());
+                        search.set_visible(true);
+                    }
+                    state_.dispatch(Action::Search(String::new()));
+                });
+            }
+
+            {
+                let ui_ = self.ui.clone();
+                search.on_close(move || {
+                    if let Some(ui) = ui_.upgrade() {
+                        ui.global::<Search>().set_visible(false);
+                    }
+                });
+            }
+
+            {
+                let (ui_, state_) = (self.ui.clone(), self.state.clone());
+                search.on_type_text(move |text| {
+                    Self::edit_query(&ui_, &state_, |query| query.push_str(&text));
+                });
+            }
+
+            {
+                let (ui_, state_) = (self.ui.clone(), self.state.clone());
+                search.on_backspace(move || {
+                    Self::edit_query(&ui_, &state_, |query| {
+                        query.pop();
+                    });
+                });
+            }
+
+            {
+                let (ui_, state_) = (self.ui.clone(), self.state.clone());
+                search.on_select(move |id| {
+                    if let Some(ui) = ui_.upgrade() {
+                        ui.global::<Search>().set_visible(false);
+                    }
+                    // Folders open and tracks play, just like tapping them in a list
+                    navigate(&ui_, &state_, Action::SelectLibraryEntry(id));
+                });
+            }
+        }
+    }
+
+    /// The query is typed on the UI side, the model only gets to see the result
+    fn edit_query(ui: &Weak<AppWindow>, state: &State, edit: impl FnOnce(&mut String)) {
+        if let Some(ui) = ui.upgrade() {
+            let search = ui.global::<Search>();
+            let mut query = search.get_query().to_string();
+            edit(&mut query);
+            search.set_query(query.clone().into());
+            state.dispatch(Action::Search(query));
+        }
+    }
+
+    pub fn setup_state_listeners(&self) {
+        let ui_weak = self.ui.clone();
+        let state = self.state.clone();
+
+        self.state.subscribe(move |changes| {
+            if !changes.iter().any(|field| matches!(field, Field::search_results(_))) {
+                return;
+            }
+
+            let query = state.search_query();
+            let results: Vec<UISearchResult> = state.search_results().iter().map(Self::map_result_to_ui).collect();
+            let ui_weak = ui_weak.clone();
+            slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_weak.upgrade() {
+                    let search = ui.global::<Search>();
+                    // Results of a query that has been typed over since are of no use
+                    if search.get_query().as_str() != query {
+                        return;
+                    }
+                    search.set_results(ModelRc::new(VecModel::from(results)));
+                }
+            }).ok();
+        });
+    }
+
+    fn map_result_to_ui(entry: &LibraryEntry) -> UISearchResult {
+        UISearchResult {
+            id: entry.id,
+            variant: format!("{:?}", entry.variant).into(),
+            name: entry.name.clone().into(),
+            parent_name: if entry.parent_id == Some(ROOT_LIBRARY_ENTRY_ID) {
+                "Home".into()
+            } else {
+                entry.parent_name.clone().unwrap_or_default().into()
+            },
+        }
+    }
+}
+
+impl Clone for SearchVM {
+    fn clone(&self) -> Self {
+        SearchVM {
+            ui: self.ui.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
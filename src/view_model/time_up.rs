@@ -0,0 +1,65 @@
+use slint::{ComponentHandle, Weak};
+
+use crate::{
+    model::{Field, State},
+    view_model::pin_pad::ask_for_pin,
+    AppWindow, TimeUp,
+};
+
+pub struct TimeUpVM {
+    ui: Weak<AppWindow>,
+    state: State,
+}
+
+impl TimeUpVM {
+    pub fn new(ui: Weak<AppWindow>, state: State) -> Self {
+        let vm = TimeUpVM { ui, state };
+        vm.setup_ui();
+        vm.setup_state_listeners();
+        vm
+    }
+
+    pub fn setup_ui(&self) {
+        if let Some(ui) = self.ui.upgrade() {
+            let time_up = ui.global::<TimeUp>();
+
+            {
+                let ui_ = self.ui.clone();
+                time_up.on_grant(move |minutes| {
+                    if let Some(ui) = ui_.upgrade() {
+                        // The minutes travel with the purpose, the PIN pad hands both to the model
+                        ask_for_pin(&ui, &format!("extra:{}", minutes), &format!("PIN für {} Minuten mehr", minutes));
+                    }
+                });
+            }
+        }
+    }
+
+    pub fn setup_state_listeners(&self) {
+        let ui_weak = self.ui.clone();
+        let state = self.state.clone();
+
+        self.state.subscribe(move |changes| {
+            if !changes.iter().any(|field| matches!(field, Field::time_is_up(_))) {
+                return;
+            }
+
+            let time_is_up = state.time_is_up();
+            let ui_weak = ui_weak.clone();
+            slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_weak.upgrade() {
+                    ui.global::<TimeUp>().set_visible(time_is_up);
+                }
+            }).ok();
+        });
+    }
+}
+
+impl Clone for TimeUpVM {
+    fn clone(&self) -> Self {
+        TimeUpVM {
+            ui: self.ui.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
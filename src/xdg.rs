@@ -0,0 +1,28 @@
+//! Where the player keeps its files, below the XDG base directories
+
+use std::path::PathBuf;
+
+/// `$XDG_CONFIG_HOME` or `~/.config`
+pub fn config_directory() -> Option<PathBuf> {
+    directory("XDG_CONFIG_HOME", ".config")
+}
+
+/// `$XDG_CACHE_HOME` or `~/.cache`
+pub fn cache_directory() -> Option<PathBuf> {
+    directory("XDG_CACHE_HOME", ".cache")
+}
+
+/// `$XDG_DATA_HOME` or `~/.local/share`
+pub fn data_directory() -> Option<PathBuf> {
+    directory("XDG_DATA_HOME", ".local/share")
+}
+
+/// The player's own directory in `$variable`, or in `fallback` below the home directory. Without
+/// a home directory, like in some service units, `fallback` is taken below the working directory.
+fn directory(variable: &str, fallback: &str) -> Option<PathBuf> {
+    let base = std::env::var_os(variable)
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))
+        .or_else(|| std::env::current_dir().ok().map(|dir| dir.join(fallback)))?;
+    Some(base.join("rust-slint-music-player"))
+}